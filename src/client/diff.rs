@@ -0,0 +1,364 @@
+use crate::client::main::request_system_info;
+use crate::daemon::snapshot::{
+    get_previous_boot_snapshot_location, render_value, VOLATILE_FIELDS_KEY,
+};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+
+pub(crate) fn main(snapshot_a: &str, snapshot_b: &str, all: bool) {
+    let old: Value = load_snapshot(snapshot_a).unwrap_or_else(|e| {
+        eprintln!("Failed to load snapshot {}: {}", snapshot_a, e);
+        std::process::exit(1);
+    });
+    let new: Value = load_snapshot(snapshot_b).unwrap_or_else(|e| {
+        eprintln!("Failed to load snapshot {}: {}", snapshot_b, e);
+        std::process::exit(1);
+    });
+
+    let changes: Vec<String> = diff_snapshots(&old, &new, all);
+    if changes.is_empty() {
+        println!("No changes");
+    }
+    for change in changes {
+        println!("{}", change);
+    }
+}
+
+fn load_snapshot(name: &str) -> Result<Value, String> {
+    let serialized: String = match name {
+        "now" => request_system_info(),
+        "boot" => {
+            fs::read_to_string(get_previous_boot_snapshot_location()).map_err(|e| e.to_string())?
+        }
+        path => fs::read_to_string(path).map_err(|e| e.to_string())?,
+    };
+    serde_yaml::from_str(&serialized).map_err(|e| e.to_string())
+}
+
+pub(crate) fn diff_snapshots(old: &Value, new: &Value, all: bool) -> Vec<String> {
+    // Either snapshot may come from an older daemon, with other collectors or none of this
+    let mut volatile: Vec<String> = vec![VOLATILE_FIELDS_KEY.to_string()];
+    if !all {
+        volatile.extend(
+            [old, new]
+                .iter()
+                .filter_map(|snapshot| snapshot.get(VOLATILE_FIELDS_KEY)?.as_sequence())
+                .flatten()
+                .filter_map(|field| field.as_str().map(str::to_string)),
+        );
+    }
+    let mut changes: Vec<String> = Vec::new();
+    diff_values("", "", old, new, &volatile, &mut changes);
+    changes
+}
+
+// `path` is what gets printed, list entries are addressed by name like `disks[/mnt/data]`,
+// while `field` leaves the names out so it can be matched against the volatile fields
+fn diff_values(
+    path: &str,
+    field: &str,
+    old: &Value,
+    new: &Value,
+    volatile: &[String],
+    changes: &mut Vec<String>,
+) {
+    if volatile.iter().any(|volatile| volatile == field) {
+        return;
+    }
+    match (old, new) {
+        (Value::Mapping(old), Value::Mapping(new)) => {
+            diff_mappings(path, field, old, new, volatile, changes);
+        }
+        (Value::Sequence(old), Value::Sequence(new)) => {
+            diff_sequences(path, field, old, new, volatile, changes);
+        }
+        (old, new) if old != new => {
+            changes.push(format!(
+                "{} {} → {}{}",
+                path,
+                render_value(old),
+                render_value(new),
+                render_delta(old, new)
+            ));
+        }
+        _ => {}
+    }
+}
+
+fn diff_mappings(
+    path: &str,
+    field: &str,
+    old: &Mapping,
+    new: &Mapping,
+    volatile: &[String],
+    changes: &mut Vec<String>,
+) {
+    // Volatile fields coming and going, like a sensor that only reads while the GPU is awake,
+    // are no more interesting than their values changing
+    let is_volatile = |field: &str| volatile.iter().any(|volatile| volatile == field);
+    for (key, old_value) in old {
+        let key: String = render_value(key);
+        let child_path: String = join(path, &key);
+        let child_field: String = join(field, &key);
+        match new.get(&key) {
+            Some(new_value) => diff_values(
+                &child_path,
+                &child_field,
+                old_value,
+                new_value,
+                volatile,
+                changes,
+            ),
+            None if is_volatile(&child_field) => {}
+            None => changes.push(format!("removed {}", child_path)),
+        }
+    }
+    for (key, new_value) in new {
+        let key: String = render_value(key);
+        if old.contains_key(&key) || is_volatile(&join(field, &key)) {
+            continue;
+        }
+        let child_path: String = join(path, &key);
+        match new_value {
+            Value::Mapping(_) | Value::Sequence(_) => changes.push(format!("new {}", child_path)),
+            value => changes.push(format!("new {} {}", child_path, render_value(value))),
+        }
+    }
+}
+
+fn diff_sequences(
+    path: &str,
+    field: &str,
+    old: &[Value],
+    new: &[Value],
+    volatile: &[String],
+    changes: &mut Vec<String>,
+) {
+    // Lists of tables with a name, like disks, are matched up by name so that a change to one
+    // entry doesn't show up as the entry being removed and added again
+    if let (Some(old_named), Some(new_named)) = (named_entries(old), named_entries(new)) {
+        for (name, old_value) in &old_named {
+            let child_path: String = format!("{}[{}]", path, name);
+            match new_named.iter().find(|(new_name, _)| new_name == name) {
+                Some((_, new_value)) => {
+                    diff_values(&child_path, field, old_value, new_value, volatile, changes)
+                }
+                None => changes.push(format!("removed {} {}", singular(path), name)),
+            }
+        }
+        for (name, _) in &new_named {
+            if !old_named.iter().any(|(old_name, _)| old_name == name) {
+                changes.push(format!("new {} {}", singular(path), name));
+            }
+        }
+        return;
+    }
+
+    for value in old.iter().filter(|value| !new.contains(value)) {
+        changes.push(format!(
+            "removed {} {}",
+            singular(path),
+            render_value(value)
+        ));
+    }
+    for value in new.iter().filter(|value| !old.contains(value)) {
+        changes.push(format!("new {} {}", singular(path), render_value(value)));
+    }
+}
+
+fn named_entries(sequence: &[Value]) -> Option<Vec<(String, &Value)>> {
+    sequence
+        .iter()
+        .map(|entry| {
+            let name: &Value = entry.as_mapping()?.get("name")?;
+            Some((render_value(name), entry))
+        })
+        .collect()
+}
+
+fn join(path: &str, key: &str) -> String {
+    match path.is_empty() {
+        true => key.to_string(),
+        false => format!("{}.{}", path, key),
+    }
+}
+
+// "new disk /mnt/data" reads better than "new disks /mnt/data"
+fn singular(path: &str) -> &str {
+    path.strip_suffix('s').unwrap_or(path)
+}
+
+fn render_delta(old: &Value, new: &Value) -> String {
+    let as_integer = |value: &Value| -> Option<i128> {
+        value
+            .as_i64()
+            .map(i128::from)
+            .or(value.as_u64().map(i128::from))
+    };
+    match (as_integer(old), as_integer(new)) {
+        (Some(old), Some(new)) => format!(" ({:+})", new - old),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(yaml: &str) -> Value {
+        serde_yaml::from_str(yaml).expect("Test snapshots are valid YAML")
+    }
+
+    #[test]
+    fn reports_changed_values() {
+        let old: Value = snapshot(
+            "
+kernel: 6.5.1
+kernel_info:
+  release: 6.5.1
+  modules: 120
+packages:
+  pacman: 812
+  flatpak: 20
+",
+        );
+        let new: Value = snapshot(
+            "
+kernel: 6.6.2
+kernel_info:
+  release: 6.6.2
+  modules: 120
+packages:
+  pacman: 826
+  flatpak: 20
+",
+        );
+        assert_eq!(
+            diff_snapshots(&old, &new, false),
+            [
+                "kernel 6.5.1 → 6.6.2",
+                "kernel_info.release 6.5.1 → 6.6.2",
+                "packages.pacman 812 → 826 (+14)",
+            ]
+        );
+        assert!(diff_snapshots(&old, &old, false).is_empty());
+    }
+
+    #[test]
+    fn reports_added_and_removed_keys() {
+        let old: Value = snapshot("hostname: box\nvirtualization: kvm\n");
+        let new: Value = snapshot("hostname: box\npackages:\n  nix-user: 3\nshell: zsh\n");
+        assert_eq!(
+            diff_snapshots(&old, &new, false),
+            ["removed virtualization", "new packages", "new shell zsh"]
+        );
+    }
+
+    #[test]
+    fn matches_named_entries_by_name() {
+        let old: Value = snapshot(
+            "
+disks:
+  - name: /
+    total: 500
+  - name: /home
+    total: 1000
+  - name: /mnt/old
+    total: 200
+",
+        );
+        // Reordered, with one entry changed, one gone and one new
+        let new: Value = snapshot(
+            "
+disks:
+  - name: /mnt/data
+    total: 4000
+  - name: /home
+    total: 2000
+  - name: /
+    total: 500
+",
+        );
+        assert_eq!(
+            diff_snapshots(&old, &new, false),
+            [
+                "disks[/home].total 1000 → 2000 (+1000)",
+                "removed disk /mnt/old",
+                "new disk /mnt/data",
+            ]
+        );
+
+        let reordered: Value = snapshot(
+            "
+disks:
+  - name: /mnt/old
+    total: 200
+  - name: /home
+    total: 1000
+  - name: /
+    total: 500
+",
+        );
+        assert!(diff_snapshots(&old, &reordered, false).is_empty());
+    }
+
+    #[test]
+    fn compares_unnamed_lists_by_value() {
+        let old: Value = snapshot("gpus:\n  - Intel UHD 620\n  - GeForce GTX 1050\n");
+        let new: Value = snapshot("gpus:\n  - GeForce GTX 1050\n  - Radeon RX 7600\n");
+        assert_eq!(
+            diff_snapshots(&old, &new, false),
+            ["removed gpu Intel UHD 620", "new gpu Radeon RX 7600"]
+        );
+    }
+
+    #[test]
+    fn skips_volatile_fields() {
+        let old: Value = snapshot(
+            "
+volatile_fields: [memory.used, disks.used, boot_time]
+boot_time: 100
+memory:
+  used: 4000
+  total: 16000
+disks:
+  - name: /
+    used: 200
+    total: 500
+",
+        );
+        let new: Value = snapshot(
+            "
+volatile_fields: [memory.used, disks.used, boot_time]
+boot_time: 200
+memory:
+  used: 5000
+  total: 32000
+disks:
+  - name: /
+    used: 300
+    total: 500
+",
+        );
+        assert_eq!(
+            diff_snapshots(&old, &new, false),
+            ["memory.total 16000 → 32000 (+16000)"]
+        );
+        assert_eq!(
+            diff_snapshots(&old, &new, true),
+            [
+                "boot_time 100 → 200 (+100)",
+                "memory.used 4000 → 5000 (+1000)",
+                "memory.total 16000 → 32000 (+16000)",
+                "disks[/].used 200 → 300 (+100)",
+            ]
+        );
+
+        // Snapshots from before the fields were listed are compared with the newer one's list
+        let older: Value = snapshot("boot_time: 100\nmemory:\n  used: 4000\n  total: 16000\n");
+        assert_eq!(
+            diff_snapshots(&older, &new, false),
+            ["memory.total 16000 → 32000 (+16000)", "new disks"]
+        );
+    }
+}
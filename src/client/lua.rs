@@ -1,8 +1,8 @@
 use crate::client::client_info::main::environmental_variable_table;
 use crate::config::main::load_lua_config;
-use crate::daemon::fetch_info::{Disk, SystemInfo};
-use crate::daemon::package_managers::Packages;
+use crate::daemon::fetch_info::SystemInfo;
 use rlua::{Context, Lua, Table};
+use serde_yaml::Value;
 
 //noinspection SpellCheckingInspection
 pub(crate) fn execute_lua(system_info: SystemInfo) -> String {
@@ -30,84 +30,40 @@ pub(crate) fn execute_lua(system_info: SystemInfo) -> String {
     fetch
 }
 
-fn system_info_table(
-    SystemInfo {
-        cpu,
-        distro,
-        motherboard,
-        kernel,
-        gpus,
-        memory,
-        disks,
-        local_ip,
-        public_ip,
-        hostname,
-        boot_time,
-        packages,
-    }: SystemInfo,
-    lua_ctx: Context,
-) -> Table {
+fn system_info_table(system_info: SystemInfo, lua_ctx: Context) -> Table {
     let table: Table = lua_ctx.create_table().unwrap();
-    table.set("distro", &*distro).unwrap();
-    table.set("cpu", &*cpu).unwrap();
-    table.set("motherboard", &*motherboard).unwrap();
-    table.set("kernel", &*kernel).unwrap();
-    let gpus_table: Table = gpu_table(gpus, lua_ctx);
-    table.set("gpus", gpus_table).unwrap();
-    let memory_table: Table = lua_ctx.create_table().unwrap();
-    memory_table.set("used", memory.used).unwrap();
-    memory_table.set("total", memory.total).unwrap();
-    table.set("memory", memory_table).unwrap();
-    let disks_table = disk_table(disks, lua_ctx);
-    table.set("disks", disks_table).unwrap();
-    table.set("local_ip", &*local_ip).unwrap();
-    table.set("public_ip", &*public_ip).unwrap();
-    table.set("hostname", &*hostname).unwrap();
-    table.set("boot_time", boot_time).unwrap();
-    let packages_table: Table = packages_table(packages, lua_ctx);
-    table.set("packages", packages_table).unwrap();
-    table
-}
-
-fn packages_table(
-    Packages {
-        pacman,
-        winget,
-        dnf,
-        apt,
-        brew,
-        emerge,
-        xbps,
-    }: Packages,
-    lua_ctx: Context,
-) -> Table {
-    let packages_table: Table = lua_ctx.create_table().unwrap();
-    packages_table.set("pacman", pacman).unwrap();
-    packages_table.set("winget", winget).unwrap();
-    packages_table.set("dnf", dnf).unwrap();
-    packages_table.set("apt", apt).unwrap();
-    packages_table.set("homebrew", brew).unwrap();
-    packages_table.set("emerge", emerge).unwrap();
-    packages_table.set("xbps", xbps).unwrap();
-    packages_table
-}
-
-fn disk_table(disks: Vec<Disk>, lua_ctx: Context) -> Table {
-    let disks_table: Table = lua_ctx.create_table().unwrap();
-    for (index, disk) in disks.iter().enumerate() {
-        let disk_table: Table = lua_ctx.create_table().unwrap();
-        disk_table.set("name", disk.name.clone()).unwrap();
-        disk_table.set("used", disk.used).unwrap();
-        disk_table.set("total", disk.total).unwrap();
-        disks_table.set(index + 1, disk_table).unwrap();
+    for (name, value) in system_info {
+        table.set(name, lua_value(value, lua_ctx)).unwrap();
     }
-    disks_table
+    table
 }
 
-fn gpu_table(gpus: Vec<String>, lua_ctx: Context) -> Table {
-    let gpus_table: Table = lua_ctx.create_table().unwrap();
-    for (index, gpu) in gpus.iter().enumerate() {
-        gpus_table.set(index + 1, gpu.clone()).unwrap();
+// Lists become tables indexed from 1, like any other lua list
+fn lua_value(value: Value, lua_ctx: Context) -> rlua::Value {
+    match value {
+        Value::Null => rlua::Value::Nil,
+        Value::Bool(bool) => rlua::Value::Boolean(bool),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => rlua::Value::Integer(integer),
+            None => rlua::Value::Number(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(string) => rlua::Value::String(lua_ctx.create_string(&string).unwrap()),
+        Value::Sequence(sequence) => {
+            let table: Table = lua_ctx.create_table().unwrap();
+            for (index, value) in sequence.into_iter().enumerate() {
+                table.set(index + 1, lua_value(value, lua_ctx)).unwrap();
+            }
+            rlua::Value::Table(table)
+        }
+        Value::Mapping(mapping) => {
+            let table: Table = lua_ctx.create_table().unwrap();
+            for (key, value) in mapping {
+                table
+                    .set(lua_value(key, lua_ctx), lua_value(value, lua_ctx))
+                    .unwrap();
+            }
+            rlua::Value::Table(table)
+        }
+        Value::Tagged(tagged) => lua_value(tagged.value, lua_ctx),
     }
-    gpus_table
 }
@@ -4,10 +4,21 @@ use crate::config::toml::{AsciiSize, TomlConfig, TOML_CONFIG_OBJECT};
 use crate::daemon::fetch_info::SystemInfo;
 use crate::{ascii_art, SOCKET_PATH};
 use interprocess::local_socket::LocalSocketStream;
-use std::borrow::Cow;
 use std::io::Read;
 
 pub(crate) fn main() {
+    let serialized: String = request_system_info();
+    let system_info: SystemInfo =
+        serde_yaml::from_str(&serialized).expect("Failed to deserialize system info");
+
+    let result: String = lua::execute_lua(system_info.clone());
+
+    let fetch: String = polish_fetch::main(&system_info, result);
+
+    println!("{}", fetch);
+}
+
+pub(crate) fn request_system_info() -> String {
     let socket_path: String = SOCKET_PATH.clone();
     let mut client: LocalSocketStream = LocalSocketStream::connect(socket_path.clone())
         .unwrap_or_else(|_| {
@@ -20,23 +31,16 @@ pub(crate) fn main() {
             );
             std::process::exit(1);
         });
-    let mut buffer: Vec<u8> = vec![0u8; 65536];
-    let bytes_read: usize = client
-        .read(&mut buffer)
+    // The daemon closes the connection once the whole fetch is sent
+    let mut buffer: Vec<u8> = Vec::new();
+    client
+        .read_to_end(&mut buffer)
         .expect("Failed to read from socket");
-    buffer.truncate(bytes_read);
-    let string: Cow<str> = String::from_utf8_lossy(&buffer);
-    let system_info: SystemInfo =
-        serde_yaml::from_str(&string).expect("Failed to deserialize system info");
-
-    let result: String = lua::execute_lua(system_info.clone());
-
-    let fetch: String = polish_fetch::main(&system_info, result);
-
-    println!("{}", fetch);
+    String::from_utf8_lossy(&buffer).to_string()
 }
 
-pub(crate) fn get_ascii_art(distro: &str) -> String {
+// Takes the distro's ID followed by the IDs it's like, so derivatives get their parent's art
+pub(crate) fn get_ascii_art(distro_ids: &[&str]) -> String {
     let config: TomlConfig = TOML_CONFIG_OBJECT.clone();
     if !config.ascii_art.ascii_art_file.is_empty() {
         return get_ascii_file(config.ascii_art.ascii_art_file);
@@ -46,14 +50,17 @@ pub(crate) fn get_ascii_art(distro: &str) -> String {
     // and forget to actually add the ascii art when creating a new logo
     this_does_nothing(ascii_art::main::ALL_ART);
 
-    let art_distro = match distro {
-        "Arch Linux" => ascii_art::main::ALL_ART.arch,
-        "Windows" => ascii_art::main::ALL_ART.windows,
-        "Ubuntu" => ascii_art::main::ALL_ART.ubuntu,
-        "Gentoo" => ascii_art::main::ALL_ART.gentoo,
-        "NixOS" => ascii_art::main::ALL_ART.nixos,
-        _ => ascii_art::main::ALL_ART.fallback,
-    };
+    let art_distro = distro_ids
+        .iter()
+        .find_map(|id| match *id {
+            "arch" => Some(ascii_art::main::ALL_ART.arch),
+            "windows" => Some(ascii_art::main::ALL_ART.windows),
+            "ubuntu" => Some(ascii_art::main::ALL_ART.ubuntu),
+            "gentoo" => Some(ascii_art::main::ALL_ART.gentoo),
+            "nixos" => Some(ascii_art::main::ALL_ART.nixos),
+            _ => None,
+        })
+        .unwrap_or(ascii_art::main::ALL_ART.fallback);
     match config.ascii_art.size {
         AsciiSize::Big => art_distro.big,
         AsciiSize::Small => art_distro.small,
@@ -1,4 +1,5 @@
 mod client_info;
+pub(crate) mod diff;
 pub(crate) mod kitty_backend;
 mod lua;
 pub mod main;
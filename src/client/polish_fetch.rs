@@ -5,6 +5,7 @@ use crate::config::toml::{
 use crate::daemon::fetch_info::SystemInfo;
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde_yaml::Value;
 use unicode_width::UnicodeWidthStr;
 
 use super::kitty_backend::get_kitty_image;
@@ -12,13 +13,26 @@ use super::kitty_backend::get_kitty_image;
 pub(crate) fn main(system_info: &SystemInfo, mut fetch: String) -> String {
     // oh boy, there is a lot of string manipulation here, I'm sorry to anyone who has to read this
     let config: &TomlConfig = &TOML_CONFIG_OBJECT;
+    let distro: Option<&Value> = system_info.get("distro_info");
+    let distro_ids: Vec<&str> = distro
+        .and_then(|distro| distro.get("id"))
+        .into_iter()
+        .chain(
+            distro
+                .and_then(|distro| distro.get("id_like"))
+                .and_then(Value::as_sequence)
+                .into_iter()
+                .flatten(),
+        )
+        .filter_map(Value::as_str)
+        .collect();
     let mut ascii_art: String;
     match config.ascii_art.backend.engine {
         crate::config::toml::Engine::Ascii => {
-            ascii_art = get_ascii_art(&system_info.distro);
+            ascii_art = get_ascii_art(&distro_ids);
         }
         crate::config::toml::Engine::Kitty => {
-            ascii_art = get_kitty_image().unwrap_or(get_ascii_art(&system_info.distro));
+            ascii_art = get_kitty_image().unwrap_or(get_ascii_art(&distro_ids));
         }
         crate::config::toml::Engine::None => {
             ascii_art = String::new();
@@ -305,7 +319,7 @@ fn align(mut blocks: Vec<&mut String>) {
                                     .map(|s| s.to_string())
                                     .collect::<Vec<String>>();
 
-                            lines.splice(0..0, empty_lines.into_iter());
+                            lines.splice(0..0, empty_lines);
                         }
                     }
                     _ => {
@@ -0,0 +1,218 @@
+use crate::config::main::load_daemon_config;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+lazy_static! {
+    pub(crate) static ref DAEMON_CONFIG_OBJECT: DaemonConfig = load_daemon_config();
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct DaemonConfig {
+    pub(crate) hooks: Vec<Hook>,
+    pub(crate) plugins: Plugins,
+    pub(crate) lua_collectors: LuaCollectors,
+    pub(crate) public_ip: PublicIp,
+    pub(crate) disks: Disks,
+    pub(crate) activity: Activity,
+    pub(crate) packages: Packages,
+}
+
+// Hooks are a list, which the default merging can't fill in, so every optional field needs
+// a serde default instead
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct Hook {
+    pub(crate) name: String,
+    pub(crate) watch: String,
+    pub(crate) relative_to: Option<String>,
+    #[serde(default = "default_trigger")]
+    pub(crate) trigger: Trigger,
+    #[serde(default)]
+    pub(crate) threshold: f64,
+    pub(crate) command: String,
+    #[serde(default = "default_debounce_seconds")]
+    pub(crate) debounce_seconds: u64,
+    #[serde(default = "default_timeout_seconds")]
+    pub(crate) timeout_seconds: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct Plugins {
+    pub(crate) directory: String,
+    pub(crate) interval_seconds: u64,
+    pub(crate) timeout_seconds: u64,
+    pub(crate) overrides: BTreeMap<String, PluginOverride>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct PluginOverride {
+    pub(crate) interval_seconds: Option<u64>,
+    pub(crate) timeout_seconds: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct LuaCollectors {
+    pub(crate) directory: String,
+    pub(crate) interval_seconds: u64,
+    pub(crate) instruction_limit: u64,
+    pub(crate) memory_limit: u64,
+    pub(crate) exec_timeout_seconds: u64,
+    pub(crate) timeout_seconds: u64,
+}
+
+// Looking up the public IP tells a third party the machine is online, so it's off by default
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct PublicIp {
+    pub(crate) enabled: bool,
+    pub(crate) ipv4_endpoints: Vec<String>,
+    pub(crate) ipv6_endpoints: Vec<String>,
+    pub(crate) interval_seconds: u64,
+    pub(crate) timeout_seconds: u64,
+}
+
+// Mounts matching any rule are left out, mount points and devices match by prefix
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct Disks {
+    pub(crate) exclude_filesystems: Vec<String>,
+    pub(crate) exclude_mount_points: Vec<String>,
+    pub(crate) exclude_devices: Vec<String>,
+    pub(crate) merge_duplicates: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct Activity {
+    pub(crate) top_processes: usize,
+    pub(crate) interval_seconds: u64,
+}
+
+// A leading `~` stands for the home directory of every user. Updates are only counted from
+// metadata that is already there, which changes far less often than the packages
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub(crate) struct Packages {
+    pub(crate) appimage_directories: Vec<String>,
+    pub(crate) updates_interval_seconds: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) enum Trigger {
+    Change,
+    Above,
+    Below,
+}
+
+fn default_trigger() -> Trigger {
+    Trigger::Change
+}
+
+fn default_debounce_seconds() -> u64 {
+    10
+}
+
+fn default_timeout_seconds() -> u64 {
+    30
+}
+
+pub(crate) fn build_default_daemon_toml() -> DaemonConfig {
+    DaemonConfig {
+        hooks: vec![],
+        plugins: Plugins {
+            directory: default_config_directory("plugins"),
+            interval_seconds: 300,
+            timeout_seconds: 10,
+            overrides: BTreeMap::new(),
+        },
+        lua_collectors: LuaCollectors {
+            directory: default_config_directory("collectors"),
+            interval_seconds: 60,
+            instruction_limit: 1_000_000,
+            memory_limit: 16 * 1024 * 1024,
+            exec_timeout_seconds: 5,
+            timeout_seconds: 15,
+        },
+        public_ip: PublicIp {
+            enabled: false,
+            ipv4_endpoints: vec![
+                "https://ipv4.icanhazip.com".to_string(),
+                "https://v4.ident.me".to_string(),
+            ],
+            ipv6_endpoints: vec![
+                "https://ipv6.icanhazip.com".to_string(),
+                "https://v6.ident.me".to_string(),
+            ],
+            interval_seconds: 3600,
+            timeout_seconds: 5,
+        },
+        disks: Disks {
+            // Pseudo filesystems, snaps and container layers, and network filesystems, which
+            // can hang when the server is gone
+            exclude_filesystems: [
+                "autofs",
+                "binfmt_misc",
+                "bpf",
+                "cgroup",
+                "cgroup2",
+                "configfs",
+                "debugfs",
+                "devpts",
+                "devtmpfs",
+                "efivarfs",
+                "fuse.gvfsd-fuse",
+                "fuse.portal",
+                "fuse.snapfuse",
+                "fusectl",
+                "hugetlbfs",
+                "mqueue",
+                "nfs",
+                "nfs4",
+                "nsfs",
+                "overlay",
+                "proc",
+                "pstore",
+                "ramfs",
+                "rootfs",
+                "rpc_pipefs",
+                "securityfs",
+                "squashfs",
+                "sysfs",
+                "tmpfs",
+                "tracefs",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+            exclude_mount_points: [
+                "/proc",
+                "/sys",
+                "/dev",
+                "/snap",
+                "/var/snap",
+                "/var/lib/docker",
+                "/var/lib/containers",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+            exclude_devices: vec![],
+            merge_duplicates: true,
+        },
+        activity: Activity {
+            top_processes: 5,
+            interval_seconds: 5,
+        },
+        packages: Packages {
+            appimage_directories: ["~/Applications", "~/AppImages"]
+                .map(str::to_string)
+                .to_vec(),
+            updates_interval_seconds: 3600,
+        },
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn default_config_directory(name: &str) -> String {
+    format!("/etc/hayabusa/{}", name)
+}
+
+#[cfg(target_os = "windows")]
+fn default_config_directory(name: &str) -> String {
+    let config_dir: String = std::env::var("PROGRAMDATA").expect("Failed to get %PROGRAMDATA%");
+    format!("{}\\hayabusa\\{}", config_dir, name)
+}
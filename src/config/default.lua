@@ -21,12 +21,159 @@ end
 -- Get GPU Information
 function gpuInfo()
     local gpu_str = ""
-    for i, gpu in ipairs(system_info.gpus) do
-        gpu_str = gpu_str .. "  " .. ansi_green .. "GPU" .. " ❯ " .. ansi_reset .. gpu .. "\n"
+    for i, gpu in ipairs(system_info.gpu_info) do
+        gpu_str = gpu_str .. "  " .. ansi_green .. "GPU" .. " ❯ " .. ansi_reset .. gpu.name
+        if gpu.vram ~= nil then
+            gpu_str = gpu_str .. " (" .. bytes_to_gib(gpu.vram.total) .. "GiB)"
+        end
+        gpu_str = gpu_str .. "\n"
     end
     return gpu_str
 end
 
+-- Get Distro, in the distro's own color when it has one
+function distroInfo()
+    local distro = system_info.distro_info
+    if distro.ansi_color ~= nil then
+        return "\27[" .. distro.ansi_color .. "m" .. distro.pretty_name .. ansi_reset
+    end
+    return distro.pretty_name
+end
+
+-- Get Kernel, with a reminder when an upgrade is waiting for a reboot
+function kernelInfo()
+    local kernel = system_info.kernel_info.release
+    if system_info.kernel_info.reboot_required then
+        kernel = kernel .. " (reboot required)"
+    end
+    return kernel
+end
+
+-- Get CPU Information, like "Ryzen 9 7950X (16C/32T) @ 5.7 GHz"
+function cpuInfo()
+    local cpu = system_info.cpu_info
+    -- Drop the noise vendors put in the brand string
+    local name = cpu.name:gsub("%(R%)", ""):gsub("%(TM%)", ""):gsub(" CPU @.*", ""):gsub(" %d+%-Core Processor", ""):gsub("^AMD ", "")
+    local cpu_str = name
+    if cpu.cores ~= nil then
+        cpu_str = cpu_str .. " (" .. cpu.cores .. "C/" .. cpu.threads .. "T)"
+    end
+    local frequency = cpu.frequency.max or cpu.frequency.current
+    if frequency ~= nil and frequency > 0 then
+        cpu_str = cpu_str .. string.format(" @ %.1f GHz", frequency / 1000)
+    end
+    return cpu_str
+end
+
+-- Get Display Information
+function displayInfo()
+    local display_str = ""
+    for i, display in ipairs(system_info.displays) do
+        display_str = display_str .. "  " .. ansi_green .. "Display ❯ " .. ansi_reset .. (display.model or display.name)
+        if display.width ~= nil then
+            display_str = display_str .. " " .. display.width .. "x" .. display.height
+        end
+        if display.refresh ~= nil then
+            display_str = display_str .. string.format(" @ %.0fHz", display.refresh)
+        end
+        display_str = display_str .. "\n"
+    end
+    return display_str
+end
+
+-- Get Temperatures, left out when there are no sensors
+function temperatureInfo()
+    local temperatures = {}
+    if system_info.sensors.cpu ~= nil then
+        table.insert(temperatures, string.format("CPU %.0f°C", system_info.sensors.cpu))
+    end
+    if system_info.sensors.gpu ~= nil then
+        table.insert(temperatures, string.format("GPU %.0f°C", system_info.sensors.gpu))
+    end
+    if #temperatures == 0 then
+        return ""
+    end
+    return "  " .. ansi_green .. "Temp ❯ " .. ansi_reset .. table.concat(temperatures, " / ") .. "\n"
+end
+
+-- Get the Machine's Vendor and Model, left out when the firmware doesn't say
+function hostInfo()
+    local system = system_info.hardware.system
+    if system.name == nil then
+        return ""
+    end
+    -- Lenovo puts a type number in the product name, the model is in the version
+    local name = system.name
+    if system.vendor == "LENOVO" and system.version ~= nil then
+        name = system.version
+    end
+    if system.vendor ~= nil then
+        name = system.vendor .. " " .. name
+    end
+    return "  " .. ansi_green .. "Host ❯ " .. ansi_reset .. name .. "\n"
+end
+
+-- Get the Hypervisor or Container, left out on bare metal
+function virtualizationInfo()
+    local virtualization = system_info.virtualization
+    if virtualization.kind == "BareMetal" or virtualization.kind == "Unknown" then
+        return ""
+    end
+    local kind = virtualization.kind == "Container" and "container" or "virtual machine"
+    local technology = virtualization.technology or "Unknown"
+    return "  " .. ansi_green .. "Virtualization ❯ " .. ansi_reset .. technology .. " " .. kind .. "\n"
+end
+
+-- Get Swap Usage, left out without swap
+function swapInfo()
+    local swap = system_info.memory.swap
+    if swap.total == 0 then
+        return ""
+    end
+    return "  " .. ansi_green .. "Swap ❯ " .. ansi_reset .. bytes_to_gib(swap.used) .. "GiB / " .. bytes_to_gib(swap.total) .. "GiB\n"
+end
+
+-- Get Load Averages, with the process count and who is logged in
+function loadInfo()
+    local activity = system_info.activity
+    if activity.load == nil then
+        return ""
+    end
+    local load = string.format("%.2f %.2f %.2f (%d processes", activity.load.one, activity.load.five, activity.load.fifteen, activity.processes)
+    if #activity.sessions > 0 then
+        load = load .. string.format(", %d sessions", #activity.sessions)
+    end
+    return "  " .. ansi_green .. "Load ❯ " .. ansi_reset .. load .. ")\n"
+end
+
+-- Get Battery Information, left out on desktops
+function batteryInfo()
+    local battery_str = ""
+    for i, battery in ipairs(system_info.batteries) do
+        battery_str = battery_str .. "  " .. ansi_green .. "Battery ❯ " .. ansi_reset
+        if battery.capacity ~= nil then
+            battery_str = battery_str .. battery.capacity .. "% "
+        end
+        battery_str = battery_str .. "[" .. battery.status .. "]"
+        if battery.time_remaining ~= nil then
+            local hours = math.floor(battery.time_remaining / 3600)
+            local minutes = math.floor(battery.time_remaining / 60) % 60
+            battery_str = battery_str .. string.format(" %dh %dm left", hours, minutes)
+        end
+        battery_str = battery_str .. "\n"
+    end
+    return battery_str
+end
+
+-- Get Public IP, the lookup is off unless enabled in the daemon config
+function publicIpInfo()
+    if system_info.public_ip_info == nil then
+        return ""
+    end
+    local address = system_info.public_ip_info.ipv4 or system_info.public_ip_info.ipv6 or "Unknown"
+    return "  " .. ansi_green .. "Public IP ❯ " .. ansi_reset .. address .. "\n"
+end
+
 -- Get Disk Information
 function diskInfo()
     local disk_str = ""
@@ -99,6 +246,28 @@ function getPackages()
     return packages
 end
 
+-- Get Pending Updates, left out when no package manager can tell
+function updatesInfo()
+    local count = nil
+    local security = nil
+    for _, package in pairs(system_info.package_info) do
+        if package.updates ~= nil then
+            count = (count or 0) + package.updates.count
+            if package.updates.security ~= nil then
+                security = (security or 0) + package.updates.security
+            end
+        end
+    end
+    if count == nil then
+        return ""
+    end
+    local updates = tostring(count)
+    if security ~= nil and security > 0 then
+        updates = updates .. " (" .. security .. " security)"
+    end
+    return "  " .. ansi_green .. "Updates ❯ " .. ansi_reset .. updates .. "\n"
+end
+
 -- [Main Configuration]
 -- ====================
 -- Edit the lines below to customize the output.
@@ -107,20 +276,28 @@ end
 local format = {
     username = "  " .. ansi_green .. "Username ❯ " .. ansi_reset .. getUsername() .. "\n",
     hostname = "  " .. ansi_green .. "Hostname ❯ " .. ansi_reset .. system_info.hostname .. "\n",
-    distro = "  " .. ansi_green .. "Distro ❯ " .. ansi_reset .. system_info.distro .. "\n",
-    cpu = "  " .. ansi_green .. "CPU ❯ " .. ansi_reset .. system_info.cpu .. "\n",
+    distro = "  " .. ansi_green .. "Distro ❯ " .. ansi_reset .. distroInfo() .. "\n",
+    cpu = "  " .. ansi_green .. "CPU ❯ " .. ansi_reset .. cpuInfo() .. "\n",
+    host = hostInfo(),
     motherboard = "  " .. ansi_green .. "Motherboard ❯ " .. ansi_reset .. system_info.motherboard .. "\n",
-    kernel = "  " .. ansi_green .. "Kernel ❯ " .. ansi_reset .. system_info.kernel .. "\n",
+    kernel = "  " .. ansi_green .. "Kernel ❯ " .. ansi_reset .. kernelInfo() .. "\n",
+    virtualization = virtualizationInfo(),
     gpu = gpuInfo(),
+    display = displayInfo(),
+    temperature = temperatureInfo(),
     memory = "  " .. ansi_green .. "Memory ❯ " .. ansi_reset .. bytes_to_gib(system_info.memory.used) .. "GiB / " .. bytes_to_gib(system_info.memory.total) .. "GiB\n",
+    swap = swapInfo(),
+    load = loadInfo(),
     disk = diskInfo(),
+    battery = batteryInfo(),
     local_ip = "  " .. ansi_green .. "Local IP ❯ " .. ansi_reset .. system_info.local_ip .. "\n",
-    public_ip = "  " .. ansi_green .. "Public IP ❯ " .. ansi_reset .. system_info.public_ip .. "\n",
+    public_ip = publicIpInfo(),
     uptime = "  " .. ansi_green .. "Uptime ❯ " .. ansi_reset .. getUptime() .. "\n",
     shell = "  " .. ansi_green .. "Shell ❯ " .. ansi_reset .. getShell() .. "\n",
     desktop_environment = "  " .. ansi_green .. "DE ❯ " .. ansi_reset .. getDE() .. "\n",
     terminal = "  " .. ansi_green .. "Terminal ❯ " .. ansi_reset .. getTerminal() .. "\n",
     packages = getPackages(),
+    updates = updatesInfo(),
 }
 
 -- System Info
@@ -130,11 +307,18 @@ local config = {
     hostname =            format.hostname,
     distro =              format.distro,
     cpu =                 format.cpu,
+    host =                format.host,
     motherboard =         format.motherboard,
     kernel =              format.kernel,
+    virtualization =      format.virtualization,
     gpu =                 format.gpu,
+    display =             format.display,
+    temperature =         format.temperature,
     memory =              format.memory,
+    swap =                format.swap,
+    load =                format.load,
     disk =                format.disk,
+    battery =             format.battery,
     local_ip =            format.local_ip,
     public_ip =           format.public_ip,
     uptime =              format.uptime,
@@ -142,6 +326,7 @@ local config = {
     desktop_environment = format.desktop_environment,
     terminal =            format.terminal,
     packages =            format.packages,
+    updates =             format.updates,
     footer = ansi_blue .. "╘═══════════════════════════════════════════╛" .. ansi_reset,
 }
 
@@ -153,15 +338,23 @@ result = config.header ..
          config.hostname ..
          config.distro ..
          config.cpu ..
+         config.host ..
          config.motherboard ..
          config.kernel ..
+         config.virtualization ..
          config.gpu ..
+         config.display ..
+         config.temperature ..
          config.memory ..
+         config.swap ..
+         config.load ..
          config.disk ..
+         config.battery ..
          config.local_ip ..
          config.uptime ..
          config.shell ..
          config.desktop_environment ..
          config.terminal ..
          config.packages ..
+         config.updates ..
          config.footer
@@ -1,4 +1,7 @@
+use crate::config::daemon::{build_default_daemon_toml, DaemonConfig};
 use crate::config::toml::{build_default_toml, TomlConfig};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::collections::BTreeMap;
 use std::path::Path;
 use std::{env, fs};
@@ -38,40 +41,50 @@ pub(crate) fn get_config_location() -> String {
 }
 
 pub(crate) fn load_toml_config() -> TomlConfig {
-    let toml_file_location: String = get_toml_config_location();
+    load_toml_file(&get_toml_config_location(), build_default_toml())
+}
+
+pub(crate) fn load_daemon_config() -> DaemonConfig {
+    load_toml_file(&get_daemon_config_location(), build_default_daemon_toml())
+}
 
+fn load_toml_file<T: Serialize + DeserializeOwned>(toml_file_location: &str, default: T) -> T {
     // Read the configuration file, if it fails, write the default toml.
-    let file_contents: String = fs::read_to_string(&toml_file_location)
+    let file_contents: String = fs::read_to_string(toml_file_location)
         .or_else(|_| {
-            write_default_toml();
-            to_string(&build_default_toml())
+            write_toml_file(toml_file_location, &default);
+            to_string(&default)
         })
         .expect("Failed to handle TOML config file.");
 
     // Try to parse the read contents directly into the struct.
-    if let Ok(config) = from_str::<TomlConfig>(&file_contents) {
+    if let Ok(config) = from_str::<T>(&file_contents) {
         config
     } else {
         // If parsing fails, merge with default and retry.
         let mut loaded_config: BTreeMap<String, Value> =
             from_str(&file_contents).expect("Failed to parse loaded config to BTreeMap.");
         let default_config_map: BTreeMap<String, Value> =
-            from_str(&to_string(&build_default_toml()).expect("Failed to serialize default TOML."))
+            from_str(&to_string(&default).expect("Failed to serialize default TOML."))
                 .expect("Failed to parse default config to BTreeMap.");
 
         let was_merged: bool = merge_maps(&mut loaded_config, &default_config_map);
         if was_merged {
             let new_config_str: String =
                 to_string(&loaded_config).expect("Failed to serialize merged config.");
-            fs::write(&toml_file_location, new_config_str)
-                .expect("Failed to update config.toml after merging.");
+            if let Err(e) = fs::write(toml_file_location, new_config_str) {
+                eprintln!(
+                    "Failed to update {} after merging: {}",
+                    toml_file_location, e
+                );
+            }
         }
 
         from_str(
             &to_string(&loaded_config)
                 .expect("Failed to serialize merged config for final struct."),
         )
-        .expect("Failed to parse final config to TomlConfig.")
+        .expect("Failed to parse final config.")
     }
 }
 
@@ -104,15 +117,16 @@ fn merge_maps(a: &mut BTreeMap<String, Value>, b: &BTreeMap<String, Value>) -> b
     was_merged
 }
 
-fn write_default_toml() {
-    let toml_file_location: String = get_toml_config_location();
-    let path: &Path = Path::new(&toml_file_location);
+// The daemon may not be allowed to write its config, it can run just fine on the defaults though
+fn write_toml_file<T: Serialize>(toml_file_location: &str, default: &T) {
+    let path: &Path = Path::new(toml_file_location);
     let parent_dir: &Path = path.parent().unwrap();
-    if !parent_dir.exists() {
-        fs::create_dir_all(parent_dir).expect("Failed to create config directory");
+    let contents: String = to_string(default).unwrap();
+    let result: std::io::Result<()> =
+        fs::create_dir_all(parent_dir).and_then(|_| fs::write(toml_file_location, contents));
+    if let Err(e) = result {
+        eprintln!("Failed to write default {}: {}", toml_file_location, e);
     }
-    let contents = to_string(&build_default_toml()).unwrap();
-    fs::write(toml_file_location, contents).expect("Failed to write default config.toml");
 }
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
@@ -127,3 +141,14 @@ pub(crate) fn get_toml_config_location() -> String {
     let config_dir: String = env::var("APPDATA").expect("Failed to get %APPDATA%");
     format!("{}\\hayabusa\\config.toml", config_dir)
 }
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn get_daemon_config_location() -> String {
+    String::from("/etc/hayabusa/daemon.toml")
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn get_daemon_config_location() -> String {
+    let config_dir: String = env::var("PROGRAMDATA").expect("Failed to get %PROGRAMDATA%");
+    format!("{}\\hayabusa\\daemon.toml", config_dir)
+}
@@ -1,2 +1,3 @@
+pub mod daemon;
 pub mod main;
 pub mod toml;
@@ -0,0 +1,271 @@
+use crate::config::daemon::{Activity as ActivityConfig, DAEMON_CONFIG_OBJECT};
+use crate::daemon::collectors::cpu::round_usage;
+use crate::daemon::collectors::main::{Collector, Refresh};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use sysinfo::{
+    PidExt, Process, ProcessExt, ProcessRefreshKind, ProcessStatus, RefreshKind, System, SystemExt,
+    UserExt,
+};
+
+// glibc and musl lay out `struct utmp` the same way on every 64-bit architecture
+#[cfg(target_os = "linux")]
+const UTMP_RECORD_LENGTH: usize = 384;
+#[cfg(target_os = "linux")]
+const UTMP_USER_PROCESS: i16 = 7;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Activity {
+    pub(crate) load: Option<Load>,
+    pub(crate) processes: usize,
+    pub(crate) running: usize,
+    pub(crate) threads: Option<u64>,
+    pub(crate) sessions: Vec<Session>,
+    pub(crate) top_cpu: Vec<ProcessInfo>,
+    pub(crate) top_memory: Vec<ProcessInfo>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Load {
+    pub(crate) one: f64,
+    pub(crate) five: f64,
+    pub(crate) fifteen: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Session {
+    pub(crate) user: String,
+    pub(crate) terminal: Option<String>,
+    pub(crate) host: Option<String>,
+    // Unix timestamp in seconds
+    pub(crate) login_time: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct ProcessInfo {
+    pub(crate) pid: u32,
+    pub(crate) name: String,
+    pub(crate) user: Option<String>,
+    // Percent of a single core, so busy processes can go over 100
+    pub(crate) cpu: f64,
+    // In bytes
+    pub(crate) memory: u64,
+}
+
+// Process CPU usage is measured against the CPUs' time since the previous refresh, which the CPU
+// collector resets every two seconds on the shared System, so the processes get their own
+pub(crate) struct ActivityCollector {
+    system: Mutex<System>,
+}
+
+impl Default for ActivityCollector {
+    // Loading the processes right away gives the first collection something to measure from
+    fn default() -> Self {
+        ActivityCollector {
+            system: Mutex::new(System::new_with_specifics(
+                RefreshKind::new()
+                    .with_processes(ProcessRefreshKind::everything())
+                    .with_users_list(),
+            )),
+        }
+    }
+}
+
+impl Collector for ActivityCollector {
+    type Output = Activity;
+
+    fn name(&self) -> &str {
+        "activity"
+    }
+
+    // CPU usage is averaged since the previous refresh, so this is also the sampling period
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(
+            DAEMON_CONFIG_OBJECT.activity.interval_seconds,
+        ))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &[
+            "load",
+            "processes",
+            "running",
+            "threads",
+            "top_cpu",
+            "top_memory",
+        ]
+    }
+
+    async fn collect(&self) -> Activity {
+        let mut system: MutexGuard<System> = self
+            .system
+            .lock()
+            .expect("Failed to lock activity sys-info mutex");
+        get_activity(&mut system, &DAEMON_CONFIG_OBJECT.activity)
+    }
+}
+
+fn get_activity(sys: &mut System, config: &ActivityConfig) -> Activity {
+    sys.refresh_processes();
+    sys.refresh_users_list();
+    let processes: Vec<ProcessInfo> = sys
+        .processes()
+        .values()
+        .map(|process| ProcessInfo {
+            pid: process.pid().as_u32(),
+            name: process.name().to_string(),
+            user: process
+                .user_id()
+                .and_then(|user_id| sys.get_user_by_id(user_id))
+                .map(|user| user.name().to_string()),
+            cpu: round_usage(process.cpu_usage()),
+            memory: process.memory(),
+        })
+        .collect();
+    let running: usize = sys
+        .processes()
+        .values()
+        .filter(|process: &&Process| process.status() == ProcessStatus::Run)
+        .count();
+    let load: Option<Load> = get_load(sys);
+
+    let mut top_cpu: Vec<ProcessInfo> = processes.clone();
+    // Ties, like all the idle processes, go by PID so the list doesn't shuffle between refreshes
+    top_cpu.sort_by(|a, b| {
+        b.cpu
+            .partial_cmp(&a.cpu)
+            .unwrap_or(Ordering::Equal)
+            .then(a.pid.cmp(&b.pid))
+    });
+    top_cpu.truncate(config.top_processes);
+    let mut top_memory: Vec<ProcessInfo> = processes.clone();
+    top_memory.sort_by_key(|process| (Reverse(process.memory), process.pid));
+    top_memory.truncate(config.top_processes);
+
+    Activity {
+        load,
+        processes: processes.len(),
+        running,
+        threads: get_threads(),
+        sessions: get_sessions(),
+        top_cpu,
+        top_memory,
+    }
+}
+
+// Windows has no load average, sysinfo reports zeros there
+#[cfg(not(target_os = "windows"))]
+fn get_load(sys: &System) -> Option<Load> {
+    let load: sysinfo::LoadAvg = sys.load_average();
+    Some(Load {
+        one: load.one,
+        five: load.five,
+        fifteen: load.fifteen,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn get_load(_sys: &System) -> Option<Load> {
+    None
+}
+
+// The fourth field of /proc/loadavg is the count of running and of all threads, like `2/731`
+#[cfg(target_os = "linux")]
+fn get_threads() -> Option<u64> {
+    let loadavg: String = fs::read_to_string("/proc/loadavg").ok()?;
+    let (_, threads) = loadavg.split_whitespace().nth(3)?.split_once('/')?;
+    threads.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_threads() -> Option<u64> {
+    None
+}
+
+// Some distributions no longer write utmp, or leave it empty, logind keeps its own list of
+// sessions there
+#[cfg(target_os = "linux")]
+fn get_sessions() -> Vec<Session> {
+    let mut sessions: Vec<Session> = fs::read("/run/utmp")
+        .map(|utmp| parse_utmp(&utmp))
+        .unwrap_or_default();
+    if sessions.is_empty() {
+        sessions = read_logind_sessions(Path::new("/run/systemd/sessions"));
+    }
+    sessions.sort_by_key(|session| session.login_time);
+    sessions
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_sessions() -> Vec<Session> {
+    vec![]
+}
+
+// Every record starts with its type, user processes are the logged in sessions, the strings
+// are padded with zeros
+#[cfg(target_os = "linux")]
+fn parse_utmp(utmp: &[u8]) -> Vec<Session> {
+    utmp.chunks_exact(UTMP_RECORD_LENGTH)
+        .filter(|record| i16::from_ne_bytes([record[0], record[1]]) == UTMP_USER_PROCESS)
+        .filter_map(|record| {
+            let login_time: i32 =
+                i32::from_ne_bytes([record[340], record[341], record[342], record[343]]);
+            Some(Session {
+                user: decode_utmp_string(&record[44..76])?,
+                terminal: decode_utmp_string(&record[8..40]),
+                host: decode_utmp_string(&record[76..332]),
+                login_time: u64::try_from(login_time).ok(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn decode_utmp_string(field: &[u8]) -> Option<String> {
+    let text: String = String::from_utf8_lossy(field.split(|byte| *byte == 0).next()?)
+        .trim()
+        .to_string();
+    match text.is_empty() {
+        true => None,
+        false => Some(text),
+    }
+}
+
+// Session files are KEY=value lines, greeters and background sessions aren't anyone logging in
+#[cfg(target_os = "linux")]
+fn read_logind_sessions(directory: &Path) -> Vec<Session> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return vec![];
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let contents: String = fs::read_to_string(entry.path()).ok()?;
+            let field = |key: &str| -> Option<String> {
+                contents
+                    .lines()
+                    .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+            };
+            if field("CLASS").is_some_and(|class| class != "user") {
+                return None;
+            }
+            Some(Session {
+                user: field("USER")?,
+                terminal: field("TTY"),
+                host: field("REMOTE_HOST"),
+                // In microseconds
+                login_time: field("REALTIME")
+                    .and_then(|realtime| realtime.parse::<u64>().ok())
+                    .map(|realtime| realtime / 1_000_000),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,33 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+use crate::daemon::fetch_info::SYS;
+use std::sync::MutexGuard;
+use sysinfo::{System, SystemExt};
+
+pub(crate) struct BootTimeCollector;
+
+impl Collector for BootTimeCollector {
+    type Output = u64;
+
+    fn name(&self) -> &str {
+        "boot_time"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    // Every reboot changes it, and it can drift by a few seconds within a boot too
+    fn volatile_fields(&self) -> &[&str] {
+        &[""]
+    }
+
+    async fn collect(&self) -> u64 {
+        get_boot_time()
+    }
+}
+
+fn get_boot_time() -> u64 {
+    let mut sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    sys.refresh_system();
+    sys.boot_time()
+}
@@ -0,0 +1,230 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::sysfs::{read_number, read_trimmed};
+use crate::daemon::fetch_info::SYS;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::collections::BTreeSet;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+use std::sync::{MutexGuard, OnceLock};
+use std::time::Duration;
+use sysinfo::{CpuExt, System, SystemExt};
+
+#[cfg(target_os = "linux")]
+const CPU_SYSFS: &str = "/sys/devices/system/cpu";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Cpu {
+    pub(crate) name: String,
+    pub(crate) vendor: String,
+    pub(crate) architecture: String,
+    pub(crate) cores: Option<usize>,
+    pub(crate) threads: usize,
+    pub(crate) sockets: Option<usize>,
+    pub(crate) frequency: Frequency,
+    pub(crate) usage: f64,
+    pub(crate) core_usage: Vec<f64>,
+    pub(crate) cache: Cache,
+    pub(crate) governor: Option<String>,
+}
+
+// In MHz
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Frequency {
+    pub(crate) current: u64,
+    pub(crate) max: Option<u64>,
+}
+
+// In bytes, the size of one cache as seen by a single core, shared caches aren't added up
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Cache {
+    pub(crate) l1d: Option<u64>,
+    pub(crate) l1i: Option<u64>,
+    pub(crate) l2: Option<u64>,
+    pub(crate) l3: Option<u64>,
+}
+
+// Usage is measured between two refreshes of the CPUs, so this collector has to be the only
+// one refreshing them
+#[derive(Default)]
+pub(crate) struct CpuCollector {
+    cpu: OnceLock<Cpu>,
+}
+
+impl Collector for CpuCollector {
+    type Output = Cpu;
+
+    fn name(&self) -> &str {
+        "cpu_info"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(2))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &["frequency.current", "usage", "core_usage"]
+    }
+
+    async fn collect(&self) -> Cpu {
+        let mut cpu: Cpu = self.cpu.get_or_init(get_cpu).clone();
+        update_cpu_usage(&mut cpu);
+        cpu
+    }
+}
+
+// `cpu` was only the name before it became a table, configs written back then still read it as
+// a string
+pub(crate) struct CpuNameCollector;
+
+impl Collector for CpuNameCollector {
+    type Output = String;
+
+    fn name(&self) -> &str {
+        "cpu"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    // The CPUs were refreshed once on startup, which is all the brand needs, refreshing them
+    // again would throw off the usage of CpuCollector
+    async fn collect(&self) -> String {
+        let sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+        sys.global_cpu_info().brand().trim().to_string()
+    }
+}
+
+fn get_cpu() -> Cpu {
+    let mut sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    sys.refresh_cpu();
+    let global: &sysinfo::Cpu = sys.global_cpu_info();
+    Cpu {
+        name: global.brand().trim().to_string(),
+        vendor: global.vendor_id().to_string(),
+        architecture: std::env::consts::ARCH.to_string(),
+        cores: sys.physical_core_count(),
+        threads: sys.cpus().len(),
+        sockets: get_sockets(),
+        frequency: Frequency {
+            current: 0,
+            max: get_max_frequency(),
+        },
+        usage: 0.0,
+        core_usage: vec![],
+        cache: get_cache(),
+        governor: get_governor(),
+    }
+}
+
+fn update_cpu_usage(cpu: &mut Cpu) {
+    let mut sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    sys.refresh_cpu();
+    cpu.usage = round_usage(sys.global_cpu_info().cpu_usage());
+    cpu.core_usage = sys
+        .cpus()
+        .iter()
+        .map(|core| round_usage(core.cpu_usage()))
+        .collect();
+    // The cores all run at their own speed, the average says the most about the whole CPU
+    let frequencies: Vec<u64> = sys.cpus().iter().map(|core| core.frequency()).collect();
+    cpu.frequency.current = match frequencies.is_empty() {
+        true => 0,
+        false => frequencies.iter().sum::<u64>() / frequencies.len() as u64,
+    };
+    cpu.governor = get_governor();
+}
+
+// One decimal is plenty, and keeps the snapshot readable, an f32 would show up with all the
+// digits of its f64 conversion
+pub(crate) fn round_usage(usage: f32) -> f64 {
+    (usage as f64 * 10.0).round() / 10.0
+}
+
+#[cfg(target_os = "linux")]
+fn get_sockets() -> Option<usize> {
+    let packages: BTreeSet<String> = fs::read_dir(CPU_SYSFS)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name: String = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix("cpu")
+                .is_some_and(|number| number.parse::<u32>().is_ok())
+        })
+        .filter_map(|entry| read_trimmed(entry.path().join("topology/physical_package_id")))
+        .collect();
+    match packages.is_empty() {
+        true => None,
+        false => Some(packages.len()),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_sockets() -> Option<usize> {
+    None
+}
+
+// The kernel reports it in kHz
+#[cfg(target_os = "linux")]
+fn get_max_frequency() -> Option<u64> {
+    let frequency: u64 = read_number(Path::new(CPU_SYSFS).join("cpu0/cpufreq/cpuinfo_max_freq"))?;
+    Some(frequency / 1000)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_max_frequency() -> Option<u64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn get_cache() -> Cache {
+    let mut cache: Cache = Cache::default();
+    let Ok(entries) = fs::read_dir(Path::new(CPU_SYSFS).join("cpu0/cache")) else {
+        return cache;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path: PathBuf = entry.path();
+        let level: Option<String> = read_trimmed(path.join("level"));
+        let kind: Option<String> = read_trimmed(path.join("type"));
+        let size: Option<u64> = read_trimmed(path.join("size")).and_then(|size| parse_size(&size));
+        match (level.as_deref(), kind.as_deref()) {
+            (Some("1"), Some("Data")) => cache.l1d = size,
+            (Some("1"), Some("Instruction")) => cache.l1i = size,
+            (Some("2"), _) => cache.l2 = size,
+            (Some("3"), _) => cache.l3 = size,
+            _ => {}
+        }
+    }
+    cache
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_cache() -> Cache {
+    Cache::default()
+}
+
+// Sizes look like `32K` or `32768K`
+#[cfg(target_os = "linux")]
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, multiplier): (&str, u64) = match size.chars().last()? {
+        'K' => (&size[..size.len() - 1], 1024),
+        'M' => (&size[..size.len() - 1], 1024 * 1024),
+        'G' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    Some(number.parse::<u64>().ok()? * multiplier)
+}
+
+#[cfg(target_os = "linux")]
+fn get_governor() -> Option<String> {
+    read_trimmed(Path::new(CPU_SYSFS).join("cpu0/cpufreq/scaling_governor"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_governor() -> Option<String> {
+    None
+}
@@ -0,0 +1,286 @@
+use crate::config::daemon::{Disks as DisksConfig, DAEMON_CONFIG_OBJECT};
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(not(target_os = "linux"))]
+use crate::daemon::fetch_info::SYS;
+#[cfg(target_os = "linux")]
+use nix::sys::statvfs::{statvfs, Statvfs};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::fs;
+use std::path::Path;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+#[cfg(target_os = "linux")]
+use std::str::{Chars, SplitWhitespace};
+#[cfg(not(target_os = "linux"))]
+use std::sync::MutexGuard;
+use std::time::Duration;
+#[cfg(not(target_os = "linux"))]
+use sysinfo::{DiskExt, System, SystemExt};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Disk {
+    pub(crate) name: String,
+    pub(crate) mount_points: Vec<String>,
+    pub(crate) device: String,
+    pub(crate) filesystem: String,
+    pub(crate) subvolumes: Vec<String>,
+    pub(crate) removable: bool,
+    pub(crate) read_only: bool,
+    pub(crate) used: u64,
+    pub(crate) total: u64,
+}
+
+// A single mount, several of which can end up as one disk when they share a filesystem
+pub(crate) struct Mount {
+    // Mounts of the same filesystem share an ID, the device number on Linux
+    pub(crate) id: String,
+    pub(crate) device: String,
+    pub(crate) filesystem: String,
+    pub(crate) mount_point: String,
+    // Which directory of the filesystem is mounted, like a btrfs subvolume or a bind mount's
+    // source, Linux only
+    pub(crate) root: Option<String>,
+    pub(crate) read_only: bool,
+    // Total and available bytes and whether it's removable, when they come with the mount
+    pub(crate) space: Option<(u64, u64)>,
+    pub(crate) removable: Option<bool>,
+}
+
+pub(crate) struct DisksCollector;
+
+impl Collector for DisksCollector {
+    type Output = Vec<Disk>;
+
+    fn name(&self) -> &str {
+        "disks"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(10))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &["used"]
+    }
+
+    async fn collect(&self) -> Vec<Disk> {
+        get_disks()
+    }
+}
+
+fn get_disks() -> Vec<Disk> {
+    get_mount_groups()
+        .into_iter()
+        .filter_map(build_disk)
+        .collect()
+}
+
+// The mounts that are left after the configured rules, grouped by filesystem with the shortest
+// mount point first
+pub(crate) fn get_mount_groups() -> Vec<Vec<Mount>> {
+    let config: &DisksConfig = &DAEMON_CONFIG_OBJECT.disks;
+    let mounts: Vec<Mount> = get_mounts()
+        .into_iter()
+        .filter(|mount| !is_excluded(config, mount))
+        .collect();
+    group_mounts(mounts, config.merge_duplicates)
+}
+
+fn is_excluded(config: &DisksConfig, mount: &Mount) -> bool {
+    config.exclude_filesystems.contains(&mount.filesystem)
+        || config
+            .exclude_mount_points
+            .iter()
+            .any(|mount_point| Path::new(&mount.mount_point).starts_with(mount_point))
+        || config
+            .exclude_devices
+            .iter()
+            .any(|device| mount.device.starts_with(device.as_str()))
+}
+
+// Mounts of one filesystem, like bind mounts or btrfs subvolumes, report the same usage, so they
+// are counted once, under their shortest mount point
+fn group_mounts(mounts: Vec<Mount>, merge_duplicates: bool) -> Vec<Vec<Mount>> {
+    let mut groups: Vec<Vec<Mount>> = Vec::new();
+    for mount in mounts {
+        let group: Option<&mut Vec<Mount>> = match merge_duplicates {
+            true => groups.iter_mut().find(|group| group[0].id == mount.id),
+            false => None,
+        };
+        match group {
+            Some(group) => group.push(mount),
+            None => groups.push(vec![mount]),
+        }
+    }
+    for group in &mut groups {
+        group.sort_by(|a, b| {
+            (a.mount_point.len(), &a.mount_point).cmp(&(b.mount_point.len(), &b.mount_point))
+        });
+    }
+    groups
+}
+
+fn build_disk(group: Vec<Mount>) -> Option<Disk> {
+    let primary: &Mount = group.first()?;
+    let (total, available) = primary.space.or_else(|| get_space(&primary.mount_point))?;
+    // Pseudo filesystems that got past the rules have no size
+    if total == 0 {
+        return None;
+    }
+    let mut subvolumes: Vec<String> = match primary.filesystem.as_str() {
+        "btrfs" => group
+            .iter()
+            .filter_map(|mount| mount.root.clone())
+            .collect(),
+        _ => vec![],
+    };
+    subvolumes.sort();
+    subvolumes.dedup();
+    Some(Disk {
+        name: primary.mount_point.clone(),
+        mount_points: group
+            .iter()
+            .map(|mount| mount.mount_point.clone())
+            .collect(),
+        device: primary.device.clone(),
+        filesystem: primary.filesystem.clone(),
+        subvolumes,
+        removable: primary
+            .removable
+            .unwrap_or_else(|| is_removable(&primary.device)),
+        read_only: group.iter().all(|mount| mount.read_only),
+        used: total.saturating_sub(available),
+        total,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn get_mounts() -> Vec<Mount> {
+    let Ok(mountinfo) = fs::read_to_string("/proc/self/mountinfo") else {
+        return vec![];
+    };
+    mountinfo.lines().filter_map(parse_mountinfo_line).collect()
+}
+
+// Lines look like `28 1 254:0 / / rw,relatime shared:1 - ext4 /dev/vda rw`, with a variable
+// number of optional fields before the dash
+#[cfg(target_os = "linux")]
+fn parse_mountinfo_line(line: &str) -> Option<Mount> {
+    let (mount_fields, filesystem_fields) = line.split_once(" - ")?;
+    let mut mount_fields: SplitWhitespace = mount_fields.split_whitespace();
+    let id: &str = mount_fields.nth(2)?;
+    let root: String = unescape(mount_fields.next()?);
+    let mount_point: String = unescape(mount_fields.next()?);
+    let mount_options: &str = mount_fields.next()?;
+    let mut filesystem_fields: SplitWhitespace = filesystem_fields.split_whitespace();
+    let filesystem: &str = filesystem_fields.next()?;
+    let device: String = unescape(filesystem_fields.next()?);
+    let super_options: &str = filesystem_fields.next().unwrap_or("");
+    Some(Mount {
+        id: id.to_string(),
+        device,
+        filesystem: filesystem.to_string(),
+        mount_point,
+        root: Some(root),
+        read_only: mount_options.split(',').any(|option| option == "ro")
+            || super_options.split(',').any(|option| option == "ro"),
+        space: None,
+        removable: None,
+    })
+}
+
+// Spaces, tabs, newlines and backslashes are escaped as three octal digits, like `\040`
+#[cfg(target_os = "linux")]
+fn unescape(field: &str) -> String {
+    let mut result: String = String::new();
+    let mut chars: Chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let digits: String = chars.clone().take(3).collect();
+        match u8::from_str_radix(&digits, 8) {
+            Ok(byte) if digits.len() == 3 => {
+                result.push(byte as char);
+                chars.nth(2);
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_mounts() -> Vec<Mount> {
+    let mut sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    sys.refresh_disks();
+    sys.disks()
+        .iter()
+        .map(|disk| {
+            let device: String = disk.name().to_string_lossy().to_string();
+            let mount_point: String = disk.mount_point().to_string_lossy().to_string();
+            Mount {
+                // Windows volumes don't always have a name
+                id: match device.is_empty() {
+                    true => mount_point.clone(),
+                    false => device.clone(),
+                },
+                device,
+                filesystem: String::from_utf8_lossy(disk.file_system()).to_string(),
+                mount_point,
+                root: None,
+                read_only: false,
+                space: Some((disk.total_space(), disk.available_space())),
+                removable: Some(disk.is_removable()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn get_space(mount_point: &str) -> Option<(u64, u64)> {
+    let stats: Statvfs = statvfs(mount_point).ok()?;
+    Some((
+        stats.blocks() * stats.fragment_size(),
+        stats.blocks_available() * stats.fragment_size(),
+    ))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_space(_mount_point: &str) -> Option<(u64, u64)> {
+    None
+}
+
+// The kernel flags removable media, USB drives often don't have removable media but are just as
+// easily unplugged
+#[cfg(target_os = "linux")]
+fn is_removable(device: &str) -> bool {
+    if !device.starts_with("/dev/") {
+        return false;
+    }
+    let Some(name) = fs::canonicalize(device)
+        .ok()
+        .and_then(|device| Some(device.file_name()?.to_os_string()))
+    else {
+        return false;
+    };
+    let Ok(block) = fs::canonicalize(Path::new("/sys/class/block").join(name)) else {
+        return false;
+    };
+    // Partitions sit inside their disk
+    let disk: PathBuf = match block.join("partition").exists() {
+        true => block.parent().map(Path::to_path_buf).unwrap_or(block),
+        false => block,
+    };
+    fs::read_to_string(disk.join("removable")).is_ok_and(|removable| removable.trim() == "1")
+        || disk
+            .components()
+            .any(|component| component.as_os_str().to_string_lossy().starts_with("usb"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_removable(_device: &str) -> bool {
+    false
+}
@@ -0,0 +1,145 @@
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::edid::{parse_edid, Edid};
+use crate::daemon::collectors::main::{Collector, Refresh};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Display {
+    pub(crate) name: String,
+    pub(crate) manufacturer: Option<String>,
+    pub(crate) model: Option<String>,
+    pub(crate) serial: Option<String>,
+    pub(crate) year: Option<u16>,
+    pub(crate) width: Option<u32>,
+    pub(crate) height: Option<u32>,
+    pub(crate) refresh: Option<f64>,
+    pub(crate) physical_size: Option<PhysicalSize>,
+}
+
+// Width and height in millimeters, the diagonal in inches like monitors are sold
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct PhysicalSize {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) diagonal: f64,
+}
+
+pub(crate) struct DisplaysCollector;
+
+impl Collector for DisplaysCollector {
+    type Output = Vec<Display>;
+
+    fn name(&self) -> &str {
+        "displays"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(10))
+    }
+
+    async fn collect(&self) -> Vec<Display> {
+        get_displays()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_displays() -> Vec<Display> {
+    read_displays(Path::new("/sys"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_displays() -> Vec<Display> {
+    vec![]
+}
+
+// Connectors sit next to their card as card<N>-<connector>, like card1-DP-1
+#[cfg(target_os = "linux")]
+fn read_displays(sys_root: &Path) -> Vec<Display> {
+    let Ok(entries) = fs::read_dir(sys_root.join("class/drm")) else {
+        return vec![];
+    };
+    let mut displays: Vec<Display> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name: String = entry.file_name().to_string_lossy().to_string();
+            let (card, connector) = file_name.split_once('-')?;
+            card.strip_prefix("card")?.parse::<u32>().ok()?;
+            read_display(&entry.path(), connector)
+        })
+        .collect();
+    displays.sort_by(|a, b| a.name.cmp(&b.name));
+    displays
+}
+
+#[cfg(target_os = "linux")]
+fn read_display(path: &Path, connector: &str) -> Option<Display> {
+    let status: String = fs::read_to_string(path.join("status")).ok()?;
+    if status.trim() != "connected" {
+        return None;
+    }
+    let edid: Option<Edid> = fs::read(path.join("edid"))
+        .ok()
+        .and_then(|edid| parse_edid(&edid));
+    // Without an EDID the preferred mode is still listed first, like `2560x1440`
+    let mode: Option<(u32, u32)> = fs::read_to_string(path.join("modes"))
+        .ok()
+        .and_then(|modes| {
+            let (width, height) = modes.lines().next()?.split_once('x')?;
+            Some((
+                width.parse().ok()?,
+                height.trim_end_matches('i').parse().ok()?,
+            ))
+        });
+
+    let Some(edid) = edid else {
+        return Some(Display {
+            name: connector.to_string(),
+            manufacturer: None,
+            model: None,
+            serial: None,
+            year: None,
+            width: mode.map(|(width, _)| width),
+            height: mode.map(|(_, height)| height),
+            refresh: None,
+            physical_size: None,
+        });
+    };
+    let physical_size: Option<PhysicalSize> =
+        edid.physical_width
+            .zip(edid.physical_height)
+            .map(|(width, height)| {
+                let diagonal: f64 = ((width * width + height * height) as f64).sqrt() / 25.4;
+                PhysicalSize {
+                    width,
+                    height,
+                    diagonal: (diagonal * 10.0).round() / 10.0,
+                }
+            });
+    Some(Display {
+        name: connector.to_string(),
+        manufacturer: Some(edid.manufacturer),
+        // Not every monitor names itself, the product code at least tells them apart
+        model: edid
+            .model
+            .or_else(|| Some(format!("{}{:04X}", edid.manufacturer_id, edid.product_code))),
+        serial: edid.serial,
+        year: Some(edid.year),
+        width: edid
+            .preferred_timing
+            .as_ref()
+            .map(|timing| timing.width)
+            .or(mode.map(|(width, _)| width)),
+        height: edid
+            .preferred_timing
+            .as_ref()
+            .map(|timing| timing.height)
+            .or(mode.map(|(_, height)| height)),
+        refresh: edid.preferred_timing.as_ref().map(|timing| timing.refresh),
+        physical_size,
+    })
+}
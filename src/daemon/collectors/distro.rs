@@ -0,0 +1,153 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(not(target_os = "linux"))]
+use crate::daemon::fetch_info::SYS;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::collections::BTreeMap;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::str::Chars;
+#[cfg(not(target_os = "linux"))]
+use std::sync::MutexGuard;
+#[cfg(not(target_os = "linux"))]
+use sysinfo::{System, SystemExt};
+
+// The distribution's file goes first, the vendor's copy is the fallback
+#[cfg(target_os = "linux")]
+const OS_RELEASE_LOCATIONS: &[&str] = &["/etc/os-release", "/usr/lib/os-release"];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Distro {
+    pub(crate) id: String,
+    pub(crate) id_like: Vec<String>,
+    pub(crate) name: String,
+    pub(crate) pretty_name: String,
+    pub(crate) version_id: Option<String>,
+    pub(crate) version_codename: Option<String>,
+    pub(crate) build_id: Option<String>,
+    pub(crate) ansi_color: Option<String>,
+    pub(crate) logo: Option<String>,
+}
+
+pub(crate) struct DistroCollector;
+
+impl Collector for DistroCollector {
+    type Output = Distro;
+
+    fn name(&self) -> &str {
+        "distro_info"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    async fn collect(&self) -> Distro {
+        get_distro()
+    }
+}
+
+// `distro` was only the name before it became a table, configs written back then still read it
+// as a string
+pub(crate) struct DistroNameCollector;
+
+impl Collector for DistroNameCollector {
+    type Output = String;
+
+    fn name(&self) -> &str {
+        "distro"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    async fn collect(&self) -> String {
+        get_distro().name
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_distro() -> Distro {
+    let fields: BTreeMap<String, String> = OS_RELEASE_LOCATIONS
+        .iter()
+        .find_map(|location| fs::read_to_string(location).ok())
+        .map(|contents| parse_os_release(&contents))
+        .unwrap_or_default();
+    let field = |key: &str| -> Option<String> {
+        fields.get(key).filter(|value| !value.is_empty()).cloned()
+    };
+
+    // The defaults are the ones the os-release spec gives for missing fields
+    let name: String = field("NAME").unwrap_or("Linux".to_string());
+    Distro {
+        id: field("ID").unwrap_or("linux".to_string()),
+        id_like: field("ID_LIKE")
+            .map(|ids| ids.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+        pretty_name: field("PRETTY_NAME").unwrap_or(name.clone()),
+        name,
+        version_id: field("VERSION_ID"),
+        version_codename: field("VERSION_CODENAME"),
+        build_id: field("BUILD_ID"),
+        ansi_color: field("ANSI_COLOR"),
+        logo: field("LOGO"),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_distro() -> Distro {
+    let mut sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    sys.refresh_system();
+    let name: String = sys.name().unwrap_or(String::from("Unknown"));
+    Distro {
+        id: std::env::consts::OS.to_string(),
+        id_like: vec![],
+        pretty_name: sys.long_os_version().unwrap_or(name.clone()),
+        name,
+        version_id: sys.os_version(),
+        version_codename: None,
+        build_id: None,
+        ansi_color: None,
+        logo: None,
+    }
+}
+
+// Lines are KEY=value, where the value may be quoted like in a shell, with backslash escapes
+// inside double quotes
+#[cfg(target_os = "linux")]
+fn parse_os_release(contents: &str) -> BTreeMap<String, String> {
+    let mut fields: BTreeMap<String, String> = BTreeMap::new();
+    for line in contents.lines() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        fields.insert(key.trim().to_string(), unquote(value.trim()));
+    }
+    fields
+}
+
+#[cfg(target_os = "linux")]
+fn unquote(value: &str) -> String {
+    let mut result: String = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars: Chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(escaped) = chars.next() {
+                    result.push(escaped);
+                }
+            }
+            (_, c) => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,493 @@
+// Decodes the 128 byte base block of an EDID, extension blocks are ignored
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+const BLOCK_LENGTH: usize = 128;
+const DESCRIPTORS: [usize; 4] = [54, 72, 90, 108];
+const DESCRIPTOR_LENGTH: usize = 18;
+
+// PNP IDs of the vendors that make most monitors and laptop panels, the rest keep their ID
+const MANUFACTURERS: &[(&str, &str)] = &[
+    ("AAC", "AcerView"),
+    ("ACR", "Acer"),
+    ("AOC", "AOC"),
+    ("APP", "Apple"),
+    ("AUO", "AU Optronics"),
+    ("AUS", "ASUS"),
+    ("BNQ", "BenQ"),
+    ("BOE", "BOE"),
+    ("CMN", "Chimei Innolux"),
+    ("DEL", "Dell"),
+    ("EIZ", "EIZO"),
+    ("ENC", "EIZO"),
+    ("GBT", "Gigabyte"),
+    ("GSM", "LG"),
+    ("HPN", "HP"),
+    ("HWP", "HP"),
+    ("IVM", "Iiyama"),
+    ("LEN", "Lenovo"),
+    ("LGD", "LG Display"),
+    ("MEI", "Panasonic"),
+    ("MSI", "MSI"),
+    ("NEC", "NEC"),
+    ("PHL", "Philips"),
+    ("SAM", "Samsung"),
+    ("SDC", "Samsung Display"),
+    ("SHP", "Sharp"),
+    ("SNY", "Sony"),
+    ("VSC", "ViewSonic"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Edid {
+    pub(crate) manufacturer_id: String,
+    pub(crate) manufacturer: String,
+    pub(crate) product_code: u16,
+    pub(crate) model: Option<String>,
+    pub(crate) serial: Option<String>,
+    pub(crate) year: u16,
+    pub(crate) preferred_timing: Option<Timing>,
+    // In millimeters
+    pub(crate) physical_width: Option<u32>,
+    pub(crate) physical_height: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Timing {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) refresh: f64,
+}
+
+pub(crate) fn parse_edid(data: &[u8]) -> Option<Edid> {
+    let block: &[u8] = data.get(..BLOCK_LENGTH)?;
+    if block[..8] != HEADER {
+        return None;
+    }
+    if block.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+        return None;
+    }
+
+    let manufacturer_id: String = decode_manufacturer_id(u16::from_be_bytes([block[8], block[9]]))?;
+    let manufacturer: String = MANUFACTURERS
+        .iter()
+        .find(|(id, _)| *id == manufacturer_id)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| manufacturer_id.clone());
+    let product_code: u16 = u16::from_le_bytes([block[10], block[11]]);
+    let serial_number: u32 = u32::from_le_bytes([block[12], block[13], block[14], block[15]]);
+
+    let mut model: Option<String> = None;
+    let mut serial: Option<String> = None;
+    let mut preferred_timing: Option<Timing> = None;
+    let mut physical_size: Option<(u32, u32)> = None;
+    for offset in DESCRIPTORS {
+        let descriptor: &[u8] = &block[offset..offset + DESCRIPTOR_LENGTH];
+        // A pixel clock means a timing, otherwise the descriptor carries text or ranges
+        if descriptor[0] != 0 || descriptor[1] != 0 {
+            // The first timing is the preferred one
+            if preferred_timing.is_none() {
+                preferred_timing = parse_timing(descriptor);
+                physical_size = parse_timing_size(descriptor);
+            }
+            continue;
+        }
+        match descriptor[3] {
+            0xfc => model = decode_text(&descriptor[5..]),
+            0xff => serial = decode_text(&descriptor[5..]),
+            _ => {}
+        }
+    }
+    // The timing's size is in millimeters, the basic one only in centimeters
+    let physical_size: Option<(u32, u32)> = physical_size.or_else(|| {
+        let (width, height) = (block[21] as u32, block[22] as u32);
+        match width > 0 && height > 0 {
+            true => Some((width * 10, height * 10)),
+            false => None,
+        }
+    });
+
+    Some(Edid {
+        manufacturer_id,
+        manufacturer,
+        product_code,
+        model,
+        serial: serial.or_else(|| match serial_number {
+            0 => None,
+            serial_number => Some(serial_number.to_string()),
+        }),
+        year: 1990 + block[17] as u16,
+        preferred_timing,
+        physical_width: physical_size.map(|(width, _)| width),
+        physical_height: physical_size.map(|(_, height)| height),
+    })
+}
+
+// Three letters of five bits each, with 1 being `A`
+fn decode_manufacturer_id(id: u16) -> Option<String> {
+    [(id >> 10) & 0x1f, (id >> 5) & 0x1f, id & 0x1f]
+        .iter()
+        .map(|letter| match letter {
+            1..=26 => Some((b'A' + *letter as u8 - 1) as char),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parse_timing(descriptor: &[u8]) -> Option<Timing> {
+    // In units of 10 kHz
+    let pixel_clock: u64 = u16::from_le_bytes([descriptor[0], descriptor[1]]) as u64 * 10_000;
+    let width: u32 = descriptor[2] as u32 | ((descriptor[4] as u32 & 0xf0) << 4);
+    let horizontal_blank: u32 = descriptor[3] as u32 | ((descriptor[4] as u32 & 0x0f) << 8);
+    let height: u32 = descriptor[5] as u32 | ((descriptor[7] as u32 & 0xf0) << 4);
+    let vertical_blank: u32 = descriptor[6] as u32 | ((descriptor[7] as u32 & 0x0f) << 8);
+    let total_pixels: u64 = (width + horizontal_blank) as u64 * (height + vertical_blank) as u64;
+    if width == 0 || height == 0 || total_pixels == 0 {
+        return None;
+    }
+    let refresh: f64 = pixel_clock as f64 / total_pixels as f64;
+    Some(Timing {
+        width,
+        height,
+        refresh: (refresh * 100.0).round() / 100.0,
+    })
+}
+
+fn parse_timing_size(descriptor: &[u8]) -> Option<(u32, u32)> {
+    let width: u32 = descriptor[12] as u32 | ((descriptor[14] as u32 & 0xf0) << 4);
+    let height: u32 = descriptor[13] as u32 | ((descriptor[14] as u32 & 0x0f) << 8);
+    match width > 0 && height > 0 {
+        true => Some((width, height)),
+        false => None,
+    }
+}
+
+// Text ends at a newline and is padded with spaces
+fn decode_text(data: &[u8]) -> Option<String> {
+    let text: String = data
+        .iter()
+        .take_while(|byte| **byte != b'\n')
+        .filter(|byte| byte.is_ascii_graphic() || **byte == b' ')
+        .map(|byte| *byte as char)
+        .collect::<String>()
+        .trim()
+        .to_string();
+    match text.is_empty() {
+        true => None,
+        false => Some(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::checked_in;
+    use serde_yaml::{Mapping, Value};
+    use std::fs;
+    use std::path::PathBuf;
+
+    // No real EDID dumps are bundled, so the blocks are put together from the spec's layout and
+    // the standard CTA-861 timings, with the checksum fixed up last
+    fn block(
+        manufacturer: u16,
+        product_code: u16,
+        serial_number: u32,
+        descriptors: [[u8; 18]; 4],
+    ) -> Vec<u8> {
+        let mut block: Vec<u8> = vec![0; BLOCK_LENGTH];
+        block[..8].copy_from_slice(&HEADER);
+        block[8..10].copy_from_slice(&manufacturer.to_be_bytes());
+        block[10..12].copy_from_slice(&product_code.to_le_bytes());
+        block[12..16].copy_from_slice(&serial_number.to_le_bytes());
+        // Week 12 of 2020, EDID 1.4, 60x34 cm
+        block[16] = 12;
+        block[17] = 30;
+        block[18..20].copy_from_slice(&[1, 4]);
+        block[21..23].copy_from_slice(&[60, 34]);
+        for (offset, descriptor) in DESCRIPTORS.iter().zip(descriptors) {
+            block[*offset..offset + DESCRIPTOR_LENGTH].copy_from_slice(&descriptor);
+        }
+        fix_checksum(&mut block);
+        block
+    }
+
+    fn fix_checksum(block: &mut [u8]) {
+        let sum: u8 = block[..127]
+            .iter()
+            .fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        block[127] = 0u8.wrapping_sub(sum);
+    }
+
+    // Pixel clock in 10 kHz units, then the active and blanking sizes and the image size in mm
+    fn timing(
+        pixel_clock: u16,
+        width: u32,
+        horizontal_blank: u32,
+        height: u32,
+        vertical_blank: u32,
+        size: (u32, u32),
+    ) -> [u8; 18] {
+        let mut descriptor: [u8; 18] = [0; 18];
+        descriptor[..2].copy_from_slice(&pixel_clock.to_le_bytes());
+        descriptor[2] = width as u8;
+        descriptor[3] = horizontal_blank as u8;
+        descriptor[4] = ((width >> 8) << 4 | horizontal_blank >> 8) as u8;
+        descriptor[5] = height as u8;
+        descriptor[6] = vertical_blank as u8;
+        descriptor[7] = ((height >> 8) << 4 | vertical_blank >> 8) as u8;
+        descriptor[12] = size.0 as u8;
+        descriptor[13] = size.1 as u8;
+        descriptor[14] = ((size.0 >> 8) << 4 | size.1 >> 8) as u8;
+        descriptor
+    }
+
+    // Up to 13 characters, ended by a newline and padded with spaces
+    fn text(tag: u8, text: &str) -> [u8; 18] {
+        let mut descriptor: [u8; 18] = [0; 18];
+        descriptor[3] = tag;
+        let mut padded: Vec<u8> = text.as_bytes().to_vec();
+        padded.push(b'\n');
+        padded.resize(13, b' ');
+        descriptor[5..].copy_from_slice(&padded);
+        descriptor
+    }
+
+    // A display range limits descriptor, which isn't decoded
+    fn range_limits() -> [u8; 18] {
+        let mut descriptor: [u8; 18] = [0; 18];
+        descriptor[3] = 0xfd;
+        descriptor[5..10].copy_from_slice(&[24, 60, 30, 140, 60]);
+        descriptor
+    }
+
+    const DEL: u16 = 0x10ac;
+    const GSM: u16 = 0x1e6d;
+
+    // 3840x2160 at 60 Hz, 594 MHz over 4400x2250 pixels
+    fn uhd_timing() -> [u8; 18] {
+        timing(59400, 3840, 560, 2160, 90, (597, 336))
+    }
+
+    #[test]
+    fn parses_a_4k_monitor() {
+        let data: Vec<u8> = block(
+            DEL,
+            0xa0fe,
+            0x4c4c4c4c,
+            [
+                uhd_timing(),
+                text(0xff, "8KQ7Y23"),
+                text(0xfc, "DELL U2720Q"),
+                range_limits(),
+            ],
+        );
+        let edid: Edid = parse_edid(&data).unwrap();
+        assert_eq!(
+            edid,
+            Edid {
+                manufacturer_id: "DEL".to_string(),
+                manufacturer: "Dell".to_string(),
+                product_code: 0xa0fe,
+                model: Some("DELL U2720Q".to_string()),
+                // The serial string goes before the number
+                serial: Some("8KQ7Y23".to_string()),
+                year: 2020,
+                preferred_timing: Some(Timing {
+                    width: 3840,
+                    height: 2160,
+                    refresh: 60.0,
+                }),
+                physical_width: Some(597),
+                physical_height: Some(336),
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_manufacturer_ids() {
+        assert_eq!(decode_manufacturer_id(DEL).as_deref(), Some("DEL"));
+        assert_eq!(decode_manufacturer_id(GSM).as_deref(), Some("GSM"));
+        assert_eq!(decode_manufacturer_id(0x0421).as_deref(), Some("AAA"));
+        assert_eq!(decode_manufacturer_id(0x6b5a).as_deref(), Some("ZZZ"));
+        // Zero and 27 to 31 aren't letters
+        assert_eq!(decode_manufacturer_id(0x0000), None);
+        assert_eq!(decode_manufacturer_id(0x041b), None);
+
+        let data: Vec<u8> = block(GSM, 0x5b09, 0, [uhd_timing(); 4]);
+        assert_eq!(parse_edid(&data).unwrap().manufacturer, "LG");
+        // Vendors that aren't in the table keep their ID, "XYZ"
+        let data: Vec<u8> = block(0x633a, 1, 0, [uhd_timing(); 4]);
+        let edid: Edid = parse_edid(&data).unwrap();
+        assert_eq!(
+            (edid.manufacturer_id.as_str(), edid.manufacturer.as_str()),
+            ("XYZ", "XYZ")
+        );
+        let data: Vec<u8> = block(0x0000, 1, 0, [uhd_timing(); 4]);
+        assert_eq!(parse_edid(&data), None);
+    }
+
+    #[test]
+    fn calculates_the_refresh_rate() {
+        let refresh = |descriptor: [u8; 18]| -> Option<f64> {
+            parse_timing(&descriptor).map(|timing| timing.refresh)
+        };
+        // 148.5 MHz over 2200x1125 is exactly 60 Hz, the NTSC variant is 148.35 MHz
+        assert_eq!(
+            refresh(timing(14850, 1920, 280, 1080, 45, (0, 0))),
+            Some(60.0)
+        );
+        assert_eq!(
+            refresh(timing(14835, 1920, 280, 1080, 45, (0, 0))),
+            Some(59.94)
+        );
+        // 2560x1440 with reduced blanking, 587.52 MHz over 2720x1500 is 144 Hz
+        assert_eq!(
+            refresh(timing(58752, 2560, 160, 1440, 60, (0, 0))),
+            Some(144.0)
+        );
+        assert_eq!(refresh(timing(14850, 0, 280, 1080, 45, (0, 0))), None);
+    }
+
+    #[test]
+    fn the_first_timing_is_preferred() {
+        let full_hd: [u8; 18] = timing(14850, 1920, 280, 1080, 45, (527, 296));
+        let data: Vec<u8> = block(
+            GSM,
+            1,
+            0,
+            [
+                uhd_timing(),
+                full_hd,
+                text(0xfc, "LG HDR 4K"),
+                range_limits(),
+            ],
+        );
+        let timing: Timing = parse_edid(&data).unwrap().preferred_timing.unwrap();
+        assert_eq!((timing.width, timing.height), (3840, 2160));
+    }
+
+    #[test]
+    fn falls_back_to_the_basic_fields() {
+        // Without a size in the timing it's the centimeters from the basic parameters, and
+        // without a serial descriptor the serial number
+        let data: Vec<u8> = block(
+            DEL,
+            0x4098,
+            12345678,
+            [
+                timing(14850, 1920, 280, 1080, 45, (0, 0)),
+                range_limits(),
+                text(0x10, ""),
+                text(0x10, ""),
+            ],
+        );
+        let edid: Edid = parse_edid(&data).unwrap();
+        assert_eq!(
+            (edid.physical_width, edid.physical_height),
+            (Some(600), Some(340))
+        );
+        assert_eq!(edid.serial.as_deref(), Some("12345678"));
+        assert_eq!(edid.model, None);
+
+        // Projectors leave the size out entirely, and a zero serial number means none
+        let mut data: Vec<u8> = block(DEL, 0x4098, 0, [range_limits(); 4]);
+        data[21..23].copy_from_slice(&[0, 0]);
+        fix_checksum(&mut data);
+        let edid: Edid = parse_edid(&data).unwrap();
+        assert_eq!((edid.physical_width, edid.physical_height), (None, None));
+        assert_eq!((edid.serial, edid.preferred_timing), (None, None));
+    }
+
+    #[test]
+    fn decodes_descriptor_text() {
+        assert_eq!(
+            decode_text(b"DELL U2720Q\n ").as_deref(),
+            Some("DELL U2720Q")
+        );
+        // Names that fill all 13 bytes have no newline
+        assert_eq!(
+            decode_text(b"ABCDEFGHIJKLM").as_deref(),
+            Some("ABCDEFGHIJKLM")
+        );
+        assert_eq!(
+            decode_text(b"  AB\x01C\n\0\0\0\0\0\0").as_deref(),
+            Some("ABC")
+        );
+        assert_eq!(decode_text(b"\n            "), None);
+    }
+
+    #[test]
+    fn rejects_broken_blocks() {
+        let data: Vec<u8> = block(DEL, 1, 0, [uhd_timing(); 4]);
+        assert!(parse_edid(&data).is_some());
+        assert_eq!(parse_edid(&data[..127]), None);
+        assert_eq!(parse_edid(&[]), None);
+
+        let mut header: Vec<u8> = data.clone();
+        header[0] = 0xff;
+        fix_checksum(&mut header);
+        assert_eq!(parse_edid(&header), None);
+
+        let mut checksum: Vec<u8> = data.clone();
+        checksum[127] = checksum[127].wrapping_add(1);
+        assert_eq!(parse_edid(&checksum), None);
+        let mut corrupted: Vec<u8> = data.clone();
+        corrupted[60] ^= 0x10;
+        assert_eq!(parse_edid(&corrupted), None);
+
+        // Extension blocks, like CTA-861 ones, don't count towards the checksum
+        let mut extended: Vec<u8> = data.clone();
+        extended[126] = 1;
+        fix_checksum(&mut extended);
+        extended.extend([0x02, 0x03].iter().chain([0xaa; 126].iter()));
+        assert!(parse_edid(&extended).is_some());
+    }
+
+    // What a dump decodes to, in the shape of the YAML file next to it
+    fn describe(edid: &Edid) -> Value {
+        let mut description: Mapping = Mapping::new();
+        description.insert("manufacturer".into(), edid.manufacturer.clone().into());
+        description.insert(
+            "model".into(),
+            serde_yaml::to_value(edid.model.clone()).unwrap(),
+        );
+        description.insert(
+            "physical_width".into(),
+            serde_yaml::to_value(edid.physical_width).unwrap(),
+        );
+        description.insert(
+            "physical_height".into(),
+            serde_yaml::to_value(edid.physical_height).unwrap(),
+        );
+        let preferred: Value = match &edid.preferred_timing {
+            Some(timing) => {
+                let mut preferred: Mapping = Mapping::new();
+                preferred.insert("width".into(), timing.width.into());
+                preferred.insert("height".into(), timing.height.into());
+                preferred.insert("refresh".into(), timing.refresh.into());
+                Value::Mapping(preferred)
+            }
+            None => Value::Null,
+        };
+        description.insert("preferred".into(), preferred);
+        Value::Mapping(description)
+    }
+
+    // Dumps read from real displays, see tests/fixtures/edid/README.md for how to add one
+    #[test]
+    fn decodes_captured_dumps() {
+        let mut dumps: Vec<PathBuf> = fs::read_dir(checked_in("edid"))
+            .expect("Failed to read the EDID fixtures")
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "bin"))
+            .collect();
+        dumps.sort();
+        for dump in dumps {
+            let expected: String = fs::read_to_string(dump.with_extension("yaml"))
+                .unwrap_or_else(|e| panic!("{} has no expectations: {}", dump.display(), e));
+            let expected: Value = serde_yaml::from_str(&expected).unwrap();
+            let edid: Edid = parse_edid(&fs::read(&dump).unwrap())
+                .unwrap_or_else(|| panic!("{} didn't parse", dump.display()));
+            assert_eq!(describe(&edid), expected, "{}", dump.display());
+        }
+    }
+}
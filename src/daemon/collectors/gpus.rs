@@ -0,0 +1,308 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::pci_ids::parse_id;
+use crate::daemon::collectors::pci_ids::{load_pci_ids, lookup_pci_names, PciNames};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::sysfs::{read_number, read_trimmed};
+use gfx_backend_vulkan::Backend;
+use gfx_hal::adapter::{Adapter, DeviceType};
+use gfx_hal::{Instance, UnsupportedBackend};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::cell::OnceCell;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// AMD doesn't say whether a GPU is an APU, but an APU's VRAM is a small carve-out of system memory
+#[cfg(target_os = "linux")]
+const INTEGRATED_VRAM_LIMIT: u64 = 2 * 1024 * 1024 * 1024;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Gpu {
+    pub(crate) name: String,
+    pub(crate) vendor: String,
+    pub(crate) vendor_id: String,
+    pub(crate) device_id: String,
+    pub(crate) subsystem: Option<String>,
+    pub(crate) driver: Option<String>,
+    pub(crate) driver_version: Option<String>,
+    pub(crate) bus_id: Option<String>,
+    pub(crate) kind: GpuKind,
+    pub(crate) vram: Option<Vram>,
+    pub(crate) busy_percent: Option<u64>,
+    pub(crate) temperature: Option<f64>,
+    pub(crate) power: Option<f64>,
+    // Where the dynamic values are read from on every refresh
+    #[serde(skip)]
+    device_path: Option<PathBuf>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Vram {
+    pub(crate) used: u64,
+    pub(crate) total: u64,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub(crate) enum GpuKind {
+    Discrete,
+    Integrated,
+    Virtual,
+    Unknown,
+}
+
+// Finding and naming the GPUs is slow and can't change, so that happens once, for both
+// collectors, and only the usage is read again on every refresh
+static GPUS: OnceLock<Vec<Gpu>> = OnceLock::new();
+
+pub(crate) struct GpusCollector;
+
+impl Collector for GpusCollector {
+    type Output = Vec<Gpu>;
+
+    fn name(&self) -> &str {
+        "gpu_info"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(2))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &["vram", "busy_percent", "temperature", "power"]
+    }
+
+    async fn collect(&self) -> Vec<Gpu> {
+        let mut gpus: Vec<Gpu> = GPUS.get_or_init(get_gpus).clone();
+        for gpu in gpus.iter_mut() {
+            update_gpu_usage(gpu);
+        }
+        gpus
+    }
+}
+
+// `gpus` was a list of names before the GPUs became tables, configs written back then still
+// read it that way
+pub(crate) struct GpuNamesCollector;
+
+impl Collector for GpuNamesCollector {
+    type Output = Vec<String>;
+
+    fn name(&self) -> &str {
+        "gpus"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    async fn collect(&self) -> Vec<String> {
+        GPUS.get_or_init(get_gpus)
+            .iter()
+            .map(|gpu| gpu.name.clone())
+            .collect()
+    }
+}
+
+// The sysfs works without any drivers or Vulkan ICDs, so Vulkan is only asked about the devices
+// it can't fully describe, or when there is no DRM device at all
+#[cfg(target_os = "linux")]
+fn get_gpus() -> Vec<Gpu> {
+    let database: Option<String> = load_pci_ids();
+    let vulkan_gpus: OnceCell<Vec<Gpu>> = OnceCell::new();
+    let vulkan_gpu = |vendor_id: &str, device_id: &str| -> Option<Gpu> {
+        vulkan_gpus
+            .get_or_init(|| get_vulkan_gpus(database.as_deref()))
+            .iter()
+            .find(|gpu| gpu.vendor_id == vendor_id && gpu.device_id == device_id)
+            .cloned()
+    };
+    let gpus: Vec<Gpu> = get_drm_gpus(Path::new("/sys"), database.as_deref(), vulkan_gpu);
+    if gpus.is_empty() {
+        return get_vulkan_gpus(database.as_deref());
+    }
+    gpus
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_gpus() -> Vec<Gpu> {
+    get_vulkan_gpus(load_pci_ids().as_deref())
+}
+
+#[cfg(target_os = "linux")]
+fn get_drm_gpus(
+    sys_root: &Path,
+    database: Option<&str>,
+    vulkan_gpu: impl Fn(&str, &str) -> Option<Gpu>,
+) -> Vec<Gpu> {
+    let entries: fs::ReadDir = match fs::read_dir(sys_root.join("class/drm")) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    // Only the cards themselves, the connectors like card0-HDMI-A-1 live right next to them
+    let mut cards: Vec<(u32, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name: String = entry.file_name().to_string_lossy().to_string();
+            let number: u32 = name.strip_prefix("card")?.parse().ok()?;
+            Some((number, entry.path().join("device")))
+        })
+        .collect();
+    cards.sort_by_key(|(number, _)| *number);
+
+    let mut gpus: Vec<Gpu> = Vec::new();
+    for (_, device) in cards {
+        // Devices without PCI IDs, like simpledrm framebuffers, aren't real GPUs
+        let Some(vendor_id) = read_id(&device.join("vendor")) else {
+            continue;
+        };
+        let Some(device_id) = read_id(&device.join("device")) else {
+            continue;
+        };
+        let subsystem_ids: Option<(u16, u16)> = read_id(&device.join("subsystem_vendor"))
+            .zip(read_id(&device.join("subsystem_device")));
+        let names: PciNames = database
+            .map(|database| lookup_pci_names(database, vendor_id, device_id, subsystem_ids))
+            .unwrap_or_default();
+        let driver: Option<String> = fs::read_link(device.join("driver"))
+            .ok()
+            .and_then(|driver| Some(driver.file_name()?.to_string_lossy().to_string()));
+        let driver_version: Option<String> = driver
+            .as_ref()
+            .and_then(|driver| read_trimmed(sys_root.join("module").join(driver).join("version")));
+        let bus_id: Option<String> = fs::canonicalize(&device)
+            .ok()
+            .and_then(|device| Some(device.file_name()?.to_string_lossy().to_string()));
+        let vram_total: Option<u64> = read_number(device.join("mem_info_vram_total"));
+        let mut kind: GpuKind = guess_kind(vendor_id, device_id, vram_total);
+
+        let vendor_id: String = format!("{:04x}", vendor_id);
+        let device_id: String = format!("{:04x}", device_id);
+        let mut name: Option<String> = names.device;
+        if name.is_none() || kind == GpuKind::Unknown {
+            if let Some(vulkan_gpu) = vulkan_gpu(&vendor_id, &device_id) {
+                name = name.or(Some(vulkan_gpu.name));
+                if kind == GpuKind::Unknown {
+                    kind = vulkan_gpu.kind;
+                }
+            }
+        }
+
+        gpus.push(Gpu {
+            name: name.unwrap_or_else(|| format!("Unknown device {}:{}", vendor_id, device_id)),
+            vendor: names.vendor.unwrap_or_else(|| vendor_id.clone()),
+            vendor_id,
+            device_id,
+            subsystem: names.subsystem,
+            driver,
+            driver_version,
+            bus_id,
+            kind,
+            vram: None,
+            busy_percent: None,
+            temperature: None,
+            power: None,
+            device_path: Some(device),
+        });
+    }
+    gpus
+}
+
+#[cfg(target_os = "linux")]
+fn guess_kind(vendor_id: u16, device_id: u16, vram_total: Option<u64>) -> GpuKind {
+    match vendor_id {
+        // Everything Intel makes is integrated, except for the Arc cards
+        0x8086 => match device_id & 0xff00 {
+            0x5600 | 0xe200 => GpuKind::Discrete,
+            _ => GpuKind::Integrated,
+        },
+        0x1002 => match vram_total {
+            Some(total) if total <= INTEGRATED_VRAM_LIMIT => GpuKind::Integrated,
+            Some(_) => GpuKind::Discrete,
+            None => GpuKind::Unknown,
+        },
+        0x10de => GpuKind::Discrete,
+        // VirtIO, VMware, QXL and Cirrus
+        0x1af4 | 0x15ad | 0x1b36 | 0x1013 => GpuKind::Virtual,
+        _ => GpuKind::Unknown,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn update_gpu_usage(gpu: &mut Gpu) {
+    let Some(device) = gpu.device_path.clone() else {
+        return;
+    };
+    gpu.vram = read_number(device.join("mem_info_vram_used"))
+        .zip(read_number(device.join("mem_info_vram_total")))
+        .map(|(used, total)| Vram { used, total });
+    gpu.busy_percent = read_number(device.join("gpu_busy_percent"));
+
+    // Drivers that report sensors put a hwmon directory under the device
+    let hwmon: Option<PathBuf> = fs::read_dir(device.join("hwmon"))
+        .ok()
+        .and_then(|mut entries| Some(entries.next()?.ok()?.path()));
+    let Some(hwmon) = hwmon else {
+        return;
+    };
+    // In millidegrees Celsius and microwatts
+    gpu.temperature = read_number(hwmon.join("temp1_input")).map(|value| value as f64 / 1000.0);
+    gpu.power = read_number(hwmon.join("power1_average"))
+        .or_else(|| read_number(hwmon.join("power1_input")))
+        .map(|value| value as f64 / 1_000_000.0);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn update_gpu_usage(_gpu: &mut Gpu) {}
+
+#[cfg(target_os = "linux")]
+fn read_id(path: &Path) -> Option<u16> {
+    parse_id(&fs::read_to_string(path).ok()?)
+}
+
+fn get_vulkan_gpus(database: Option<&str>) -> Vec<Gpu> {
+    let instance: Result<gfx_backend_vulkan::Instance, UnsupportedBackend> =
+        Instance::create("hayabusa", 1);
+    if instance.is_err() {
+        return vec![];
+    }
+    let instance: gfx_backend_vulkan::Instance = instance.unwrap();
+    let adapters: Vec<Adapter<Backend>> = instance.enumerate_adapters();
+
+    let mut gpus: Vec<Gpu> = Vec::new();
+
+    for adapter in adapters {
+        let vendor_id: u16 = adapter.info.vendor as u16;
+        let device_id: u16 = adapter.info.device as u16;
+        let vendor: Option<String> = database
+            .and_then(|database| lookup_pci_names(database, vendor_id, device_id, None).vendor);
+        let kind: GpuKind = match adapter.info.device_type {
+            DeviceType::DiscreteGpu => GpuKind::Discrete,
+            DeviceType::IntegratedGpu => GpuKind::Integrated,
+            DeviceType::VirtualGpu => GpuKind::Virtual,
+            DeviceType::Cpu | DeviceType::Other => GpuKind::Unknown,
+        };
+        gpus.push(Gpu {
+            name: adapter.info.name.to_string(),
+            vendor: vendor.unwrap_or_else(|| format!("{:04x}", vendor_id)),
+            vendor_id: format!("{:04x}", vendor_id),
+            device_id: format!("{:04x}", device_id),
+            subsystem: None,
+            driver: None,
+            driver_version: None,
+            bus_id: None,
+            kind,
+            vram: None,
+            busy_percent: None,
+            temperature: None,
+            power: None,
+            device_path: None,
+        });
+    }
+    gpus
+}
@@ -0,0 +1,238 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+
+// What board makers leave in the DMI tables when they don't fill them in
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+const PLACEHOLDERS: &[&str] = &[
+    "To Be Filled By O.E.M.",
+    "To be filled by O.E.M.",
+    "Default string",
+    "System manufacturer",
+    "System Product Name",
+    "System Version",
+    "Not Applicable",
+    "Not Specified",
+    "Not Defined",
+    "None",
+    "N/A",
+    "0123456789",
+    "x.x",
+];
+
+// SMBIOS chassis types, in order from 1
+#[cfg(target_os = "linux")]
+const CHASSIS_TYPES: &[&str] = &[
+    "Other",
+    "Unknown",
+    "Desktop",
+    "Low Profile Desktop",
+    "Pizza Box",
+    "Mini Tower",
+    "Tower",
+    "Portable",
+    "Laptop",
+    "Notebook",
+    "Hand Held",
+    "Docking Station",
+    "All in One",
+    "Sub Notebook",
+    "Space-saving",
+    "Lunch Box",
+    "Main Server Chassis",
+    "Expansion Chassis",
+    "SubChassis",
+    "Bus Expansion Chassis",
+    "Peripheral Chassis",
+    "RAID Chassis",
+    "Rack Mount Chassis",
+    "Sealed-case PC",
+    "Multi-system Chassis",
+    "Compact PCI",
+    "Advanced TCA",
+    "Blade",
+    "Blade Enclosure",
+    "Tablet",
+    "Convertible",
+    "Detachable",
+    "IoT Gateway",
+    "Embedded PC",
+    "Mini PC",
+    "Stick PC",
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Hardware {
+    pub(crate) system: Product,
+    pub(crate) board: Board,
+    pub(crate) chassis: Option<Chassis>,
+    pub(crate) bios: Bios,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Product {
+    pub(crate) vendor: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) version: Option<String>,
+    pub(crate) family: Option<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Board {
+    pub(crate) vendor: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) version: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Chassis {
+    pub(crate) code: u8,
+    pub(crate) name: String,
+    pub(crate) category: ChassisCategory,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) enum ChassisCategory {
+    Desktop,
+    Laptop,
+    Tablet,
+    Server,
+    Other,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Bios {
+    pub(crate) vendor: Option<String>,
+    pub(crate) version: Option<String>,
+    pub(crate) date: Option<String>,
+}
+
+pub(crate) struct HardwareCollector;
+
+impl Collector for HardwareCollector {
+    type Output = Hardware;
+
+    fn name(&self) -> &str {
+        "hardware"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    async fn collect(&self) -> Hardware {
+        get_hardware()
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn get_hardware() -> Hardware {
+    read_hardware(Path::new("/"))
+}
+
+// Windows keeps a copy of the SMBIOS strings in the registry, but not the chassis
+#[cfg(target_os = "windows")]
+pub(crate) fn get_hardware() -> Hardware {
+    use winreg::{enums::HKEY_LOCAL_MACHINE, RegKey};
+
+    let bios_key: Option<RegKey> = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(r"HARDWARE\DESCRIPTION\System\BIOS")
+        .ok();
+    let field = |name: &str| -> Option<String> {
+        let value: String = bios_key.as_ref()?.get_value(name).ok()?;
+        clean(&value)
+    };
+    Hardware {
+        system: Product {
+            vendor: field("SystemManufacturer"),
+            name: field("SystemProductName"),
+            version: field("SystemVersion"),
+            family: field("SystemFamily"),
+        },
+        board: Board {
+            vendor: field("BaseBoardManufacturer"),
+            name: field("BaseBoardProduct"),
+            version: field("BaseBoardVersion"),
+        },
+        chassis: None,
+        bios: Bios {
+            vendor: field("BIOSVendor"),
+            version: field("BIOSVersion"),
+            date: field("BIOSReleaseDate"),
+        },
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn get_hardware() -> Hardware {
+    Hardware {
+        system: Product::default(),
+        board: Board::default(),
+        chassis: None,
+        bios: Bios::default(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_hardware(root: &Path) -> Hardware {
+    let dmi: &Path = &root.join("sys/class/dmi/id");
+    let field = |name: &str| -> Option<String> { clean(&fs::read_to_string(dmi.join(name)).ok()?) };
+    let mut system: Product = Product {
+        vendor: field("sys_vendor"),
+        name: field("product_name"),
+        version: field("product_version"),
+        family: field("product_family"),
+    };
+    // ARM boards usually have no DMI tables, but their device tree names the board, ending in a
+    // NUL byte
+    if system.name.is_none() {
+        system.name = fs::read_to_string(root.join("proc/device-tree/model"))
+            .ok()
+            .and_then(|model| clean(model.trim_end_matches('\0')));
+    }
+    Hardware {
+        system,
+        board: Board {
+            vendor: field("board_vendor"),
+            name: field("board_name"),
+            version: field("board_version"),
+        },
+        chassis: field("chassis_type")
+            .and_then(|code| code.parse::<u8>().ok())
+            .and_then(decode_chassis),
+        bios: Bios {
+            vendor: field("bios_vendor"),
+            version: field("bios_version"),
+            date: field("bios_date"),
+        },
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn decode_chassis(code: u8) -> Option<Chassis> {
+    let name: &str = CHASSIS_TYPES.get(code.checked_sub(1)? as usize)?;
+    let category: ChassisCategory = match code {
+        3..=7 | 13 | 15 | 16 | 24 | 35 | 36 => ChassisCategory::Desktop,
+        8..=10 | 14 | 31 | 32 => ChassisCategory::Laptop,
+        11 | 30 => ChassisCategory::Tablet,
+        17 | 23 | 25 | 28 | 29 => ChassisCategory::Server,
+        _ => ChassisCategory::Other,
+    };
+    Some(Chassis {
+        code,
+        name: name.to_string(),
+        category,
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn clean(value: &str) -> Option<String> {
+    let value: &str = value.trim();
+    match value.is_empty() || PLACEHOLDERS.contains(&value) {
+        true => None,
+        false => Some(value.to_string()),
+    }
+}
@@ -0,0 +1,55 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use std::process::{Command, Output};
+use std::time::Duration;
+
+pub(crate) struct HostnameCollector;
+
+impl Collector for HostnameCollector {
+    type Output = String;
+
+    fn name(&self) -> &str {
+        "hostname"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(10))
+    }
+
+    async fn collect(&self) -> String {
+        get_hostname()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_hostname() -> String {
+    use std::fs;
+    fs::read_to_string("/etc/hostname")
+        .unwrap_or(String::from("Unknown"))
+        .trim()
+        .to_string()
+}
+
+#[cfg(target_os = "macos")]
+fn get_hostname() -> String {
+    let output_raw: Result<Output, std::io::Error> = Command::new("hostname").arg("-f").output();
+
+    let out = match output_raw {
+        Err(_) => {
+            return "Unknown".to_string();
+        }
+        Ok(x) => {
+            String::from_utf8(x.stdout).expect("non-utf8 response found from call to hostname")
+        }
+    };
+    out.trim().to_owned()
+}
+
+#[cfg(target_os = "windows")]
+fn get_hostname() -> String {
+    let output: Output = Command::new("hostname")
+        .output()
+        .expect("Failed to execute command");
+
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
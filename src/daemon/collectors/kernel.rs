@@ -0,0 +1,201 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::sysfs::read_trimmed;
+#[cfg(not(target_os = "linux"))]
+use crate::daemon::fetch_info::SYS;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+#[cfg(not(target_os = "linux"))]
+use std::sync::MutexGuard;
+use std::time::Duration;
+#[cfg(not(target_os = "linux"))]
+use sysinfo::{System, SystemExt};
+
+// The bits of /proc/sys/kernel/tainted, in order
+#[cfg(target_os = "linux")]
+const TAINT_FLAGS: &[&str] = &[
+    "proprietary_module",
+    "forced_module",
+    "out_of_spec",
+    "forced_unload",
+    "machine_check",
+    "bad_page",
+    "user_request",
+    "died_recently",
+    "acpi_override",
+    "warning",
+    "staging_driver",
+    "firmware_workaround",
+    "out_of_tree_module",
+    "unsigned_module",
+    "soft_lockup",
+    "live_patched",
+    "auxiliary",
+    "randstruct",
+    "test",
+];
+
+// Where distributions keep the modules of their installed kernels
+#[cfg(target_os = "linux")]
+const MODULE_DIRECTORIES: &[&str] = &["/lib/modules", "/usr/lib/modules"];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Kernel {
+    pub(crate) name: String,
+    pub(crate) release: String,
+    pub(crate) version: Option<String>,
+    pub(crate) architecture: String,
+    pub(crate) cmdline: Option<String>,
+    pub(crate) tainted: u64,
+    pub(crate) taint_flags: Vec<String>,
+    pub(crate) modules: Option<usize>,
+    pub(crate) init: Option<String>,
+    pub(crate) reboot_required: bool,
+}
+
+pub(crate) struct KernelCollector;
+
+impl Collector for KernelCollector {
+    type Output = Kernel;
+
+    fn name(&self) -> &str {
+        "kernel_info"
+    }
+
+    // The kernel can't change without a reboot, but taints, modules and upgrades can
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(60))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &["modules"]
+    }
+
+    async fn collect(&self) -> Kernel {
+        get_kernel()
+    }
+}
+
+// `kernel` was only the release before it became a table, configs written back then still read
+// it as a string
+pub(crate) struct KernelReleaseCollector;
+
+impl Collector for KernelReleaseCollector {
+    type Output = String;
+
+    fn name(&self) -> &str {
+        "kernel"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    async fn collect(&self) -> String {
+        get_release()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_kernel() -> Kernel {
+    let release: String = get_release();
+    let tainted: u64 = read_trimmed("/proc/sys/kernel/tainted")
+        .and_then(|tainted| tainted.parse().ok())
+        .unwrap_or(0);
+    Kernel {
+        name: read_trimmed("/proc/sys/kernel/ostype").unwrap_or("Linux".to_string()),
+        version: read_trimmed("/proc/sys/kernel/version"),
+        // Only newer kernels have the arch file, the daemon's own architecture is the same
+        architecture: read_trimmed("/proc/sys/kernel/arch")
+            .unwrap_or(std::env::consts::ARCH.to_string()),
+        cmdline: read_trimmed("/proc/cmdline"),
+        tainted,
+        taint_flags: TAINT_FLAGS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| tainted & (1 << bit) != 0)
+            .map(|(_, flag)| flag.to_string())
+            .collect(),
+        modules: fs::read_to_string("/proc/modules")
+            .ok()
+            .map(|modules| modules.lines().count()),
+        init: get_init_system(),
+        reboot_required: is_reboot_required(&release),
+        release,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_kernel() -> Kernel {
+    // These are read when asked for, refreshing the system would also reset the CPU usage
+    let sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    Kernel {
+        name: sys.name().unwrap_or(String::from("Unknown")),
+        release: sys.kernel_version().unwrap_or(String::from("Unknown")),
+        version: None,
+        architecture: std::env::consts::ARCH.to_string(),
+        cmdline: None,
+        tainted: 0,
+        taint_flags: vec![],
+        modules: None,
+        init: None,
+        reboot_required: false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_release() -> String {
+    read_trimmed("/proc/sys/kernel/osrelease").unwrap_or("Unknown".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_release() -> String {
+    let sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    sys.kernel_version().unwrap_or(String::from("Unknown"))
+}
+
+// Most init systems are PID 1 themselves, OpenRC runs under sysvinit's init and leaves a
+// directory in /run instead
+#[cfg(target_os = "linux")]
+fn get_init_system() -> Option<String> {
+    if Path::new("/run/systemd/system").is_dir() {
+        return Some("systemd".to_string());
+    }
+    if Path::new("/run/openrc").is_dir() {
+        return Some("OpenRC".to_string());
+    }
+    let init: String = read_trimmed("/proc/1/comm")?;
+    Some(
+        match init.as_str() {
+            "runit" => "runit",
+            "s6-svscan" => "s6",
+            other => other,
+        }
+        .to_string(),
+    )
+}
+
+// Package managers delete the modules of the kernel they replace, so a running kernel without
+// modules has been upgraded underneath
+#[cfg(target_os = "linux")]
+fn is_reboot_required(release: &str) -> bool {
+    // NixOS keeps its modules elsewhere, but links the booted and the current kernel
+    if Path::new("/run/current-system").exists() {
+        let booted: Option<PathBuf> = fs::read_link("/run/booted-system/kernel").ok();
+        let current: Option<PathBuf> = fs::read_link("/run/current-system/kernel").ok();
+        return booted.is_some() && current.is_some() && booted != current;
+    }
+    let directories: Vec<&Path> = MODULE_DIRECTORIES
+        .iter()
+        .map(Path::new)
+        .filter(|directory| directory.is_dir())
+        .collect();
+    // Containers don't have any modules, that says nothing about the kernel
+    !directories.is_empty()
+        && !directories
+            .iter()
+            .any(|directory| directory.join(release).is_dir())
+}
@@ -0,0 +1,34 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+use crate::daemon::collectors::network::{derive_local_ip, get_interfaces};
+use local_ip_address::local_ip;
+use std::time::Duration;
+
+pub(crate) struct LocalIpCollector;
+
+impl Collector for LocalIpCollector {
+    type Output = String;
+
+    fn name(&self) -> &str {
+        "local_ip"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(10))
+    }
+
+    async fn collect(&self) -> String {
+        get_local_ip_address()
+    }
+}
+
+// Derived from the interfaces, the guess of the local_ip_address crate is only used when none
+// of them has an IPv4 address
+fn get_local_ip_address() -> String {
+    match derive_local_ip(&get_interfaces())
+        .ok_or(())
+        .or_else(|_| local_ip())
+    {
+        Ok(ip) => ip.to_string(),
+        Err(_) => "Unknown".to_string(),
+    }
+}
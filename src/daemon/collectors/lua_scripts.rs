@@ -0,0 +1,448 @@
+use crate::config::daemon::{LuaCollectors, DAEMON_CONFIG_OBJECT};
+use crate::daemon::collectors::main::{error_value, Collector, Refresh};
+use rlua::{Context, HookTriggers, Lua, StdLib, Table};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// How often the instruction count is checked, checking on every instruction would be wasteful
+const INSTRUCTION_STEP: u32 = 1000;
+// Deeper tables than this are most likely a table that contains itself
+const MAX_TABLE_DEPTH: usize = 32;
+
+// Runs a lua script from the collector directory inside the daemon, whatever table the script
+// returns is put into the snapshot under `custom.<script>`
+pub(crate) struct LuaCollector {
+    name: String,
+    path: PathBuf,
+    interval: Duration,
+}
+
+impl Collector for LuaCollector {
+    type Output = Value;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(self.interval)
+    }
+
+    async fn collect(&self) -> Value {
+        let path: PathBuf = self.path.clone();
+        // Lua runs synchronously, so it gets its own thread to not stall the other collectors
+        let result: Result<Value, String> = tokio::task::spawn_blocking(move || {
+            run_script(&path, &DAEMON_CONFIG_OBJECT.lua_collectors)
+        })
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+        result.unwrap_or_else(|e| {
+            eprintln!("Lua collector {} failed: {}", self.path.display(), e);
+            error_value(e)
+        })
+    }
+}
+
+pub(crate) fn discover_scripts() -> Vec<LuaCollector> {
+    let config: &LuaCollectors = &DAEMON_CONFIG_OBJECT.lua_collectors;
+    let entries: fs::ReadDir = match fs::read_dir(&config.directory) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut scripts: Vec<LuaCollector> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "lua"))
+        .filter_map(|path| {
+            // Dots would nest the script one level deeper than the rest
+            let name: String = path.file_stem()?.to_string_lossy().replace('.', "_");
+            Some(LuaCollector {
+                name: format!("custom.{}", name),
+                path,
+                interval: Duration::from_secs(config.interval_seconds),
+            })
+        })
+        .collect();
+    scripts.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    scripts
+}
+
+fn run_script(path: &Path, config: &LuaCollectors) -> Result<Value, String> {
+    let script: String = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    // No io or os library, scripts get to the system through the functions below only
+    let lua: Lua =
+        Lua::new_with(StdLib::BASE | StdLib::TABLE | StdLib::STRING | StdLib::UTF8 | StdLib::MATH);
+    lua.set_memory_limit(Some(config.memory_limit as usize));
+    let instruction_limit: u64 = config.instruction_limit;
+    let mut instructions: u64 = 0;
+    // The instructions only count time spent in Lua, a script calling `exec` in a loop would get
+    // around them, so there's a deadline for the whole run too
+    let deadline: Deadline = Deadline::after(Duration::from_secs(config.timeout_seconds));
+    lua.set_hook(
+        HookTriggers {
+            every_nth_instruction: Some(INSTRUCTION_STEP),
+            ..Default::default()
+        },
+        move |_, _| {
+            instructions += INSTRUCTION_STEP as u64;
+            if instructions > instruction_limit {
+                return Err(rlua::Error::RuntimeError(format!(
+                    "exceeded the limit of {} instructions",
+                    instruction_limit
+                )));
+            }
+            deadline.check()
+        },
+    );
+
+    let exec_timeout: Duration = Duration::from_secs(config.exec_timeout_seconds);
+    lua.context(|lua_ctx| {
+        register_api(lua_ctx, deadline, exec_timeout).map_err(|e| e.to_string())?;
+        let result: rlua::Value = lua_ctx
+            .load(&script)
+            .set_name(&path.to_string_lossy().to_string())
+            .and_then(|chunk| chunk.eval())
+            .map_err(describe_error)?;
+        match result {
+            rlua::Value::Table(_) => yaml_value(result, 0),
+            _ => Err("script did not return a table".to_string()),
+        }
+    })
+}
+
+// Errors raised by the instruction limit come back wrapped, the wrapper only has a traceback
+fn describe_error(error: rlua::Error) -> String {
+    match error {
+        rlua::Error::CallbackError { cause, .. } => cause.to_string(),
+        error => error.to_string(),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Deadline {
+    at: Instant,
+    limit: Duration,
+}
+
+impl Deadline {
+    fn after(limit: Duration) -> Deadline {
+        Deadline {
+            at: Instant::now() + limit,
+            limit,
+        }
+    }
+
+    fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    fn check(&self) -> rlua::Result<()> {
+        match self.remaining().is_zero() {
+            true => Err(rlua::Error::RuntimeError(format!(
+                "exceeded the time limit of {:?}",
+                self.limit
+            ))),
+            false => Ok(()),
+        }
+    }
+}
+
+fn register_api(lua_ctx: Context, deadline: Deadline, exec_timeout: Duration) -> rlua::Result<()> {
+    let globals: Table = lua_ctx.globals();
+
+    // read_file(path) returns the contents, or nil and the error
+    let read_file = lua_ctx.create_function(move |_, path: String| {
+        deadline.check()?;
+        Ok(match fs::read_to_string(path) {
+            Ok(contents) => (Some(contents), None),
+            Err(e) => (None, Some(e.to_string())),
+        })
+    })?;
+    globals.set("read_file", read_file)?;
+
+    // exec(command) returns stdout and the exit code, or nil, nil and the error. A command is
+    // cut short when the script runs out of time, which ends the script too
+    let exec = lua_ctx.create_function(move |_, command: String| {
+        deadline.check()?;
+        let result: Result<(String, i32), String> =
+            exec_command(&command, exec_timeout.min(deadline.remaining()));
+        deadline.check()?;
+        Ok(match result {
+            Ok((stdout, code)) => (Some(stdout), Some(code), None),
+            Err(e) => (None, None, Some(e)),
+        })
+    })?;
+    globals.set("exec", exec)?;
+    Ok(())
+}
+
+fn exec_command(command: &str, timeout: Duration) -> Result<(String, i32), String> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let mut command_builder: Command = Command::new("sh");
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    command_builder.arg("-c");
+    #[cfg(target_os = "windows")]
+    let mut command_builder: Command = Command::new("cmd");
+    #[cfg(target_os = "windows")]
+    command_builder.arg("/C");
+    // In a group of its own, so whatever the command starts can be killed along with it
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    std::os::unix::process::CommandExt::process_group(&mut command_builder, 0);
+
+    let mut child: Child = command_builder
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // The output has to be read while waiting, a full pipe would block the command forever
+    let mut stdout = child.stdout.take().expect("stdout was piped");
+    let reader: JoinHandle<String> = std::thread::spawn(move || {
+        let mut output: String = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+
+    let start: Instant = Instant::now();
+    let status: ExitStatus = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if start.elapsed() >= timeout {
+            kill_process_group(&mut child);
+            let _ = child.wait();
+            return Err(format!("timed out after {:?}", timeout));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    // Anything left running in the background would hold on to stdout, and outlive the script
+    kill_process_group(&mut child);
+    let output: String = reader.join().unwrap_or_default();
+    Ok((output, status.code().unwrap_or(-1)))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn kill_process_group(child: &mut Child) {
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid;
+    let _ = killpg(Pid::from_raw(child.id() as i32), Signal::SIGKILL);
+}
+
+#[cfg(target_os = "windows")]
+fn kill_process_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+// Tables with only the keys 1 to n are lists, anything else becomes a map
+fn yaml_value(value: rlua::Value, depth: usize) -> Result<Value, String> {
+    if depth > MAX_TABLE_DEPTH {
+        return Err(format!(
+            "tables nest deeper than {} levels",
+            MAX_TABLE_DEPTH
+        ));
+    }
+    Ok(match value {
+        rlua::Value::Nil => Value::Null,
+        rlua::Value::Boolean(bool) => Value::Bool(bool),
+        rlua::Value::Integer(integer) => Value::from(integer),
+        rlua::Value::Number(number) => Value::from(number),
+        rlua::Value::String(string) => {
+            Value::from(string.to_str().map_err(|e| e.to_string())?.to_string())
+        }
+        rlua::Value::Table(table) => {
+            let length: usize = table.raw_len() as usize;
+            let mut entries: Vec<(Value, Value)> = Vec::new();
+            for pair in table.pairs::<rlua::Value, rlua::Value>() {
+                let (key, value) = pair.map_err(|e| e.to_string())?;
+                entries.push((yaml_value(key, depth + 1)?, yaml_value(value, depth + 1)?));
+            }
+            match entries.len() == length && length > 0 {
+                true => {
+                    entries.sort_by_key(|(key, _)| key.as_u64());
+                    Value::Sequence(entries.into_iter().map(|(_, value)| value).collect())
+                }
+                false => Value::Mapping(entries.into_iter().collect::<Mapping>()),
+            }
+        }
+        other => return Err(format!("cannot store a {} value", other.type_name())),
+    })
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    fn config() -> LuaCollectors {
+        LuaCollectors {
+            directory: String::new(),
+            interval_seconds: 60,
+            instruction_limit: 1_000_000,
+            memory_limit: 16 * 1024 * 1024,
+            exec_timeout_seconds: 1,
+            timeout_seconds: 3,
+        }
+    }
+
+    fn run(script: &str, config: &LuaCollectors) -> Result<Value, String> {
+        let fixture: Fixture = Fixture::new();
+        run_script(&fixture.write("script.lua", script), config)
+    }
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn returns_tables() {
+        let fixture: Fixture = Fixture::new();
+        let temperature: PathBuf = fixture.write("temp", "45000\n");
+        let script: String = format!(
+            r#"
+            local temperature = read_file("{}")
+            local missing, error = read_file("{}")
+            return {{
+                temperature = tonumber(temperature) / 1000,
+                missing = missing == nil and error ~= nil,
+                zones = {{ "acpitz", "x86_pkg_temp" }},
+                nested = {{ fans = {{}} }},
+            }}
+            "#,
+            temperature.display(),
+            fixture.path().join("missing").display()
+        );
+        assert_eq!(
+            run(&script, &config()),
+            Ok(yaml(
+                "{temperature: 45.0, missing: true, zones: [acpitz, x86_pkg_temp], nested: {fans: {}}}"
+            ))
+        );
+        assert_eq!(
+            run("return 42", &config()),
+            Err("script did not return a table".to_string())
+        );
+        assert_eq!(
+            run("return { print }", &config()),
+            Err("cannot store a function value".to_string())
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_system_libraries() {
+        let script: &str = r#"
+            return {
+                os = type(os),
+                io = type(io),
+                require = type(require),
+                package = type(package),
+                debug = type(debug),
+            }
+        "#;
+        assert_eq!(
+            run(script, &config()),
+            Ok(yaml(
+                "{os: nil, io: nil, require: nil, package: nil, debug: nil}"
+            ))
+        );
+        assert!(run("return { os.getenv('HOME') }", &config()).is_err());
+    }
+
+    #[test]
+    fn stops_at_the_instruction_limit() {
+        assert_eq!(
+            run("while true do end", &config()),
+            Err("runtime error: exceeded the limit of 1000000 instructions".to_string())
+        );
+        // The limit is for the whole run, not per call
+        let script: &str = r#"
+            local function count(n) local total = 0 for i = 1, n do total = total + i end return total end
+            for _ = 1, 100 do count(10000) end
+            return {}
+        "#;
+        assert!(run(script, &config()).is_err());
+    }
+
+    #[test]
+    fn stops_at_the_memory_limit() {
+        let script: &str = r#"
+            local parts = {}
+            for i = 1, 100000 do parts[i] = string.rep("x", 1000) .. i end
+            return {}
+        "#;
+        let error: String = run(script, &config()).unwrap_err();
+        assert!(error.contains("memory"), "{error}");
+    }
+
+    #[test]
+    fn times_out_commands() {
+        assert_eq!(
+            run(
+                r#"local out, code = exec("echo hello; exit 3") return { out = out, code = code }"#,
+                &config()
+            ),
+            Ok(yaml("{out: \"hello\\n\", code: 3}"))
+        );
+
+        let started: Instant = Instant::now();
+        let script: &str = r#"
+            local out, code, error = exec("sleep 30")
+            return { out = out, code = code, error = error }
+        "#;
+        assert_eq!(
+            run(script, &config()),
+            Ok(yaml("{error: timed out after 1s}"))
+        );
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn limits_the_whole_run() {
+        // Every command stays under its own timeout, together they'd take a minute
+        let started: Instant = Instant::now();
+        let script: &str = r#"
+            for _ = 1, 60 do exec("sleep 0.9") end
+            return {}
+        "#;
+        assert_eq!(
+            run(script, &config()),
+            Err("runtime error: exceeded the time limit of 3s".to_string())
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn kills_what_commands_start() {
+        let fixture: Fixture = Fixture::new();
+        let pid_file: PathBuf = fixture.path().join("pid");
+        let script: String = format!(
+            r#"
+            local _, _, error = exec("sh -c 'echo $$ > {}; exec sleep 30' & sleep 30")
+            return {{ error = error }}
+            "#,
+            pid_file.display()
+        );
+        assert_eq!(
+            run(&script, &config()),
+            Ok(yaml("{error: timed out after 1s}"))
+        );
+
+        let pid: String = fs::read_to_string(&pid_file).unwrap();
+        // Killed, at most waiting for whoever adopted it to reap it
+        let stat: String =
+            fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+        let state: Option<&str> = stat
+            .rsplit_once(')')
+            .and_then(|(_, rest)| rest.split_whitespace().next());
+        assert!(matches!(state, None | Some("Z")), "{stat}");
+    }
+}
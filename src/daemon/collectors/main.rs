@@ -0,0 +1,158 @@
+use crate::config::daemon::DAEMON_CONFIG_OBJECT;
+use crate::daemon::collectors::{
+    activity, boot_time, cpu, disks, displays, distro, gpus, hardware, hostname, kernel, local_ip,
+    lua_scripts, memory, motherboard, network, plugins, power_supply, public_ip, sensors, storage,
+    virtualization,
+};
+use crate::daemon::package_managers::main::{PackageInfoCollector, PackagesCollector};
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+// A collector is one source of system information, it ends up in the snapshot under its name,
+// dots in the name nest it into a table
+pub(crate) trait Collector: Send + Sync + 'static {
+    type Output: Serialize + Send;
+
+    fn name(&self) -> &str;
+    fn refresh(&self) -> Refresh;
+    fn collect(&self) -> impl Future<Output = Self::Output> + Send;
+
+    // Fields of the output, as dotted paths without list names, that differ between any two
+    // snapshots, so that diffs can leave them out. An empty path stands for the whole output
+    fn volatile_fields(&self) -> &[&str] {
+        &[]
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Refresh {
+    // Values that cannot realistically change during runtime are only collected on startup
+    Once,
+    Every(Duration),
+}
+
+pub(crate) type CollectFuture<'a> = Pin<Box<dyn Future<Output = Value> + Send + 'a>>;
+
+// The registry has to hold collectors with different outputs side by side, so this is the same
+// trait with the output already serialized
+pub(crate) trait DynCollector: Send + Sync {
+    fn name(&self) -> &str;
+    fn refresh(&self) -> Refresh;
+    fn volatile_fields(&self) -> &[&str];
+    fn collect_value(&self) -> CollectFuture<'_>;
+}
+
+impl<T: Collector> DynCollector for T {
+    fn name(&self) -> &str {
+        Collector::name(self)
+    }
+
+    fn refresh(&self) -> Refresh {
+        Collector::refresh(self)
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        Collector::volatile_fields(self)
+    }
+
+    fn collect_value(&self) -> CollectFuture<'_> {
+        Box::pin(async move {
+            serde_yaml::to_value(self.collect().await).unwrap_or_else(|e| {
+                eprintln!("Failed to serialize {}: {}", Collector::name(self), e);
+                Value::Null
+            })
+        })
+    }
+}
+
+// Collectors that can fail in ways the user should know about report it in place of their table
+pub(crate) fn error_value(error: String) -> Value {
+    let mut mapping: Mapping = Mapping::new();
+    mapping.insert(Value::from("error"), Value::from(error));
+    Value::Mapping(mapping)
+}
+
+#[derive(Default)]
+pub(crate) struct CollectorRegistry {
+    collectors: Vec<Arc<dyn DynCollector>>,
+}
+
+impl CollectorRegistry {
+    // Plugins and scripts are named after their files, so two of them can end up with the same
+    // name, the first one keeps it rather than both overwriting each other's table
+    pub(crate) fn register<T: Collector>(&mut self, collector: T) {
+        let name: &str = Collector::name(&collector);
+        if self.collectors.iter().any(|other| other.name() == name) {
+            eprintln!(
+                "Skipping a collector named {}, another collector already has that name",
+                name
+            );
+            return;
+        }
+        self.collectors.push(Arc::new(collector));
+    }
+
+    pub(crate) fn collectors(&self) -> &[Arc<dyn DynCollector>] {
+        &self.collectors
+    }
+
+    // Every collector's volatile fields, prefixed with where the collector sits in the snapshot
+    pub(crate) fn volatile_fields(&self) -> Vec<String> {
+        self.collectors
+            .iter()
+            .flat_map(|collector| {
+                collector
+                    .volatile_fields()
+                    .iter()
+                    .map(|field| match field.is_empty() {
+                        true => collector.name().to_string(),
+                        false => format!("{}.{}", collector.name(), field),
+                    })
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn build_registry() -> CollectorRegistry {
+    let mut registry: CollectorRegistry = CollectorRegistry::default();
+    registry.register(cpu::CpuCollector::default());
+    registry.register(cpu::CpuNameCollector);
+    registry.register(distro::DistroCollector);
+    registry.register(distro::DistroNameCollector);
+    registry.register(motherboard::MotherboardCollector);
+    registry.register(hardware::HardwareCollector);
+    registry.register(virtualization::VirtualizationCollector);
+    registry.register(kernel::KernelCollector);
+    registry.register(kernel::KernelReleaseCollector);
+    registry.register(gpus::GpusCollector);
+    registry.register(gpus::GpuNamesCollector);
+    registry.register(displays::DisplaysCollector);
+    registry.register(memory::MemoryCollector);
+    registry.register(sensors::SensorsCollector);
+    registry.register(power_supply::BatteriesCollector);
+    registry.register(power_supply::AcPowerCollector);
+    registry.register(disks::DisksCollector);
+    registry.register(storage::StorageCollector);
+    registry.register(network::NetworkCollector::default());
+    registry.register(local_ip::LocalIpCollector);
+    registry.register(public_ip::PublicIpAddressCollector);
+    if DAEMON_CONFIG_OBJECT.public_ip.enabled {
+        registry.register(public_ip::PublicIpCollector);
+    }
+    registry.register(hostname::HostnameCollector);
+    registry.register(boot_time::BootTimeCollector);
+    registry.register(activity::ActivityCollector::default());
+    registry.register(PackagesCollector::default());
+    registry.register(PackageInfoCollector::default());
+    for plugin in plugins::discover_plugins() {
+        registry.register(plugin);
+    }
+    for script in lua_scripts::discover_scripts() {
+        registry.register(script);
+    }
+    registry
+}
@@ -0,0 +1,282 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::sysfs::{read_number, read_trimmed};
+use crate::daemon::fetch_info::SYS;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::collections::BTreeMap;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "linux")]
+use std::str::SplitWhitespace;
+use std::sync::MutexGuard;
+use std::time::Duration;
+use sysinfo::{System, SystemExt};
+
+// Everything is in bytes
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Memory {
+    pub(crate) used: u64,
+    pub(crate) total: u64,
+    pub(crate) available: u64,
+    pub(crate) free: u64,
+    pub(crate) buffers: Option<u64>,
+    pub(crate) cached: Option<u64>,
+    pub(crate) swap: Swap,
+    pub(crate) zram: Vec<Zram>,
+    pub(crate) zswap: Option<Zswap>,
+    pub(crate) huge_pages: Option<HugePages>,
+    pub(crate) pressure: Option<Pressure>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Swap {
+    pub(crate) used: u64,
+    pub(crate) total: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Zram {
+    pub(crate) name: String,
+    pub(crate) algorithm: Option<String>,
+    pub(crate) size: u64,
+    pub(crate) original: u64,
+    pub(crate) compressed: u64,
+    pub(crate) used: u64,
+    pub(crate) ratio: Option<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Zswap {
+    pub(crate) enabled: bool,
+    pub(crate) compressor: Option<String>,
+    pub(crate) original: Option<u64>,
+    pub(crate) compressed: Option<u64>,
+    pub(crate) ratio: Option<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct HugePages {
+    pub(crate) total: u64,
+    pub(crate) free: u64,
+    pub(crate) size: u64,
+}
+
+// Percentages of time some or all tasks were stalled waiting on memory, averaged over 10, 60
+// and 300 seconds
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Pressure {
+    pub(crate) some: PressureAverages,
+    pub(crate) full: PressureAverages,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct PressureAverages {
+    pub(crate) avg10: f64,
+    pub(crate) avg60: f64,
+    pub(crate) avg300: f64,
+}
+
+pub(crate) struct MemoryCollector;
+
+impl Collector for MemoryCollector {
+    type Output = Memory;
+
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(1))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &[
+            "used",
+            "available",
+            "free",
+            "buffers",
+            "cached",
+            "swap.used",
+            "zram",
+            "zswap.original",
+            "zswap.compressed",
+            "zswap.ratio",
+            "huge_pages.free",
+            "pressure",
+        ]
+    }
+
+    async fn collect(&self) -> Memory {
+        get_memory()
+    }
+}
+
+fn get_memory() -> Memory {
+    let mut sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    sys.refresh_memory();
+    let mut memory: Memory = Memory {
+        used: sys.used_memory(),
+        total: sys.total_memory(),
+        available: sys.available_memory(),
+        free: sys.free_memory(),
+        buffers: None,
+        cached: None,
+        swap: Swap {
+            used: sys.used_swap(),
+            total: sys.total_swap(),
+        },
+        zram: vec![],
+        zswap: None,
+        huge_pages: None,
+        pressure: None,
+    };
+    drop(sys);
+    add_linux_details(&mut memory);
+    memory
+}
+
+#[cfg(target_os = "linux")]
+fn add_linux_details(memory: &mut Memory) {
+    let meminfo: BTreeMap<String, u64> = read_meminfo();
+    memory.buffers = meminfo.get("Buffers").copied();
+    memory.cached = meminfo.get("Cached").copied();
+    memory.zram = get_zram_devices();
+    memory.zswap = get_zswap(&meminfo);
+    memory.huge_pages = meminfo
+        .get("HugePages_Total")
+        .zip(meminfo.get("HugePages_Free"))
+        .zip(meminfo.get("Hugepagesize"))
+        .map(|((total, free), size)| HugePages {
+            total: *total,
+            free: *free,
+            size: *size,
+        });
+    memory.pressure = get_pressure();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn add_linux_details(_memory: &mut Memory) {}
+
+// Sizes are in kB, which are turned into bytes, the huge page counts have no unit
+#[cfg(target_os = "linux")]
+fn read_meminfo() -> BTreeMap<String, u64> {
+    let Ok(meminfo) = fs::read_to_string("/proc/meminfo") else {
+        return BTreeMap::new();
+    };
+    meminfo
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let mut parts: SplitWhitespace = value.split_whitespace();
+            let number: u64 = parts.next()?.parse().ok()?;
+            let multiplier: u64 = match parts.next() {
+                Some("kB") => 1024,
+                _ => 1,
+            };
+            Some((key.to_string(), number * multiplier))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn get_zram_devices() -> Vec<Zram> {
+    let Ok(entries) = fs::read_dir("/sys/block") else {
+        return vec![];
+    };
+    let mut devices: Vec<Zram> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("zram"))
+        .filter_map(|entry| read_zram(&entry.path()))
+        .collect();
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    devices
+}
+
+// mm_stat starts with the original size, the compressed size and the memory used to store it
+#[cfg(target_os = "linux")]
+fn read_zram(path: &Path) -> Option<Zram> {
+    let size: u64 = read_number(path.join("disksize"))?;
+    // Devices that were never set up have no size
+    if size == 0 {
+        return None;
+    }
+    let stats: Vec<u64> = read_trimmed(path.join("mm_stat"))?
+        .split_whitespace()
+        .filter_map(|stat| stat.parse().ok())
+        .collect();
+    let (original, compressed, used) = (*stats.first()?, *stats.get(1)?, *stats.get(2)?);
+    // The algorithm in use is the one in brackets, like `lzo [lz4] zstd`
+    let algorithm: Option<String> = read_trimmed(path.join("comp_algorithm")).and_then(|list| {
+        let selected: &str = list.split('[').nth(1)?.split(']').next()?;
+        Some(selected.to_string())
+    });
+    Some(Zram {
+        name: path.file_name()?.to_string_lossy().to_string(),
+        algorithm,
+        size,
+        original,
+        compressed,
+        used,
+        ratio: compression_ratio(original, compressed),
+    })
+}
+
+// The pool sizes are only in /proc/meminfo on 5.19 and later
+#[cfg(target_os = "linux")]
+fn get_zswap(meminfo: &BTreeMap<String, u64>) -> Option<Zswap> {
+    let parameters: PathBuf = PathBuf::from("/sys/module/zswap/parameters");
+    let enabled: String = read_trimmed(parameters.join("enabled"))?;
+    let original: Option<u64> = meminfo.get("Zswapped").copied();
+    let compressed: Option<u64> = meminfo.get("Zswap").copied();
+    Some(Zswap {
+        enabled: enabled == "Y",
+        compressor: read_trimmed(parameters.join("compressor")),
+        original,
+        compressed,
+        ratio: original
+            .zip(compressed)
+            .and_then(|(original, compressed)| compression_ratio(original, compressed)),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn compression_ratio(original: u64, compressed: u64) -> Option<f64> {
+    match compressed {
+        0 => None,
+        compressed => Some((original as f64 / compressed as f64 * 100.0).round() / 100.0),
+    }
+}
+
+// Lines look like `some avg10=0.00 avg60=0.00 avg300=0.00 total=0`
+#[cfg(target_os = "linux")]
+fn get_pressure() -> Option<Pressure> {
+    let pressure: String = fs::read_to_string("/proc/pressure/memory").ok()?;
+    let mut averages: BTreeMap<&str, PressureAverages> = BTreeMap::new();
+    for line in pressure.lines() {
+        let mut fields: SplitWhitespace = line.split_whitespace();
+        let Some(kind) = fields.next() else {
+            continue;
+        };
+        let mut line_averages: PressureAverages = PressureAverages::default();
+        for field in fields {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            let value: f64 = value.parse().unwrap_or(0.0);
+            match key {
+                "avg10" => line_averages.avg10 = value,
+                "avg60" => line_averages.avg60 = value,
+                "avg300" => line_averages.avg300 = value,
+                _ => {}
+            }
+        }
+        averages.insert(kind, line_averages);
+    }
+    Some(Pressure {
+        some: averages.remove("some")?,
+        full: averages.remove("full").unwrap_or_default(),
+    })
+}
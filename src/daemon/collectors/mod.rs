@@ -0,0 +1,26 @@
+pub(crate) mod activity;
+pub(crate) mod boot_time;
+pub(crate) mod cpu;
+pub(crate) mod disks;
+pub(crate) mod displays;
+pub(crate) mod distro;
+pub(crate) mod edid;
+pub(crate) mod gpus;
+pub(crate) mod hardware;
+pub(crate) mod hostname;
+pub(crate) mod kernel;
+pub(crate) mod local_ip;
+pub(crate) mod lua_scripts;
+pub(crate) mod main;
+pub(crate) mod memory;
+pub(crate) mod motherboard;
+pub(crate) mod network;
+pub(crate) mod pci_ids;
+pub(crate) mod plugins;
+pub(crate) mod power_supply;
+pub(crate) mod public_ip;
+pub(crate) mod sensors;
+pub(crate) mod storage;
+#[cfg(target_os = "linux")]
+pub(crate) mod sysfs;
+pub(crate) mod virtualization;
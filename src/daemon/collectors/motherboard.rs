@@ -0,0 +1,84 @@
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::hardware::{get_hardware, Hardware};
+use crate::daemon::collectors::main::{Collector, Refresh};
+use crate::daemon::collectors::virtualization::{
+    get_virtualization, Virtualization, VirtualizationKind,
+};
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use std::process::{Command, Output};
+
+pub(crate) struct MotherboardCollector;
+
+impl Collector for MotherboardCollector {
+    type Output = String;
+
+    fn name(&self) -> &str {
+        "motherboard"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    // A hypervisor's board is made up, like QEMU's `440FX`, so only the hypervisor is named
+    async fn collect(&self) -> String {
+        let virtualization: Virtualization = get_virtualization();
+        match virtualization.kind {
+            VirtualizationKind::VirtualMachine => format!(
+                "{} virtual machine",
+                virtualization.technology.unwrap_or("Unknown".to_string())
+            ),
+            _ => get_motherboard(),
+        }
+    }
+}
+
+// Boards without DMI tables, like most ARM boards, are named by their device tree instead
+#[cfg(target_os = "linux")]
+fn get_motherboard() -> String {
+    let hardware: Hardware = get_hardware();
+    hardware
+        .board
+        .name
+        .or(hardware.system.name)
+        .unwrap_or(String::from("Unknown"))
+}
+
+#[cfg(target_os = "macos")]
+fn get_motherboard() -> String {
+    let output_raw: Result<Output, std::io::Error> = Command::new("system_profiler")
+        .arg("SPHardwareDataType")
+        .output();
+
+    let output: String = match output_raw {
+        Err(_) => {
+            return "Unknown".to_string();
+        }
+        Ok(x) => String::from_utf8(x.stdout)
+            .expect("non-utf8 response found from call to system_proflier"),
+    };
+
+    output
+        .split("\n")
+        .into_iter()
+        .filter(|x| x.contains("Model Number:"))
+        .map(|y| y.replace("Model Number:", ""))
+        .map(|z| z.trim().to_string())
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn get_motherboard() -> String {
+    use winreg::{enums::HKEY_LOCAL_MACHINE, RegKey};
+
+    let local_machine_key: RegKey = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let path: &str = r"SYSTEM\HardwareConfig\Current";
+
+    match local_machine_key.open_subkey(path) {
+        Ok(sub_key) => match sub_key.get_value("BaseBoardProduct") {
+            Ok(name) => name,
+            Err(_) => String::from("Unknown"),
+        },
+        Err(_) => String::from("Unknown"),
+    }
+}
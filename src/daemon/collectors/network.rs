@@ -0,0 +1,302 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+use crate::daemon::fetch_info::SYS;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use nix::ifaddrs::{getifaddrs, InterfaceAddress};
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use nix::net::if_::InterfaceFlags;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(target_os = "linux")]
+use std::fs;
+use std::net::IpAddr;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use sysinfo::{NetworkExt, System, SystemExt};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Interface {
+    pub(crate) name: String,
+    pub(crate) mac: Option<String>,
+    pub(crate) up: bool,
+    pub(crate) loopback: bool,
+    pub(crate) default_route: bool,
+    pub(crate) speed: Option<u64>,
+    pub(crate) ipv4: Vec<String>,
+    pub(crate) ipv6: Vec<String>,
+    pub(crate) rx_rate: Option<u64>,
+    pub(crate) tx_rate: Option<u64>,
+}
+
+// The received and transmitted byte counters of every interface at one point in time
+struct Sample {
+    time: Instant,
+    bytes: BTreeMap<String, (u64, u64)>,
+}
+
+// Rates are worked out from the counters of the previous collection, so the first one has none
+#[derive(Default)]
+pub(crate) struct NetworkCollector {
+    previous: Mutex<Option<Sample>>,
+}
+
+impl Collector for NetworkCollector {
+    type Output = Vec<Interface>;
+
+    fn name(&self) -> &str {
+        "interfaces"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(5))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &["rx_rate", "tx_rate"]
+    }
+
+    async fn collect(&self) -> Vec<Interface> {
+        let mut interfaces: Vec<Interface> = get_interfaces();
+        let sample: Sample = take_sample();
+        let mut previous: MutexGuard<Option<Sample>> = self
+            .previous
+            .lock()
+            .expect("Failed to lock network sample mutex");
+        if let Some(previous) = previous.as_ref() {
+            let seconds: f64 = sample.time.duration_since(previous.time).as_secs_f64();
+            for interface in interfaces.iter_mut() {
+                let Some(((rx, tx), (previous_rx, previous_tx))) = sample
+                    .bytes
+                    .get(&interface.name)
+                    .zip(previous.bytes.get(&interface.name))
+                else {
+                    continue;
+                };
+                // Counters start over when an interface is recreated
+                interface.rx_rate = rx
+                    .checked_sub(*previous_rx)
+                    .map(|bytes| (bytes as f64 / seconds) as u64);
+                interface.tx_rate = tx
+                    .checked_sub(*previous_tx)
+                    .map(|bytes| (bytes as f64 / seconds) as u64);
+            }
+        }
+        *previous = Some(sample);
+        interfaces
+    }
+}
+
+fn take_sample() -> Sample {
+    let mut sys: MutexGuard<System> = SYS.lock().expect("Failed to lock sys-info mutex");
+    sys.refresh_networks_list();
+    Sample {
+        time: Instant::now(),
+        bytes: sys
+            .networks()
+            .into_iter()
+            .map(|(name, data)| {
+                (
+                    name.to_string(),
+                    (data.total_received(), data.total_transmitted()),
+                )
+            })
+            .collect(),
+    }
+}
+
+// Every interface with its addresses, without the rates
+pub(crate) fn get_interfaces() -> Vec<Interface> {
+    let mut interfaces: Vec<Interface> = list_interfaces();
+    let default_routes: Vec<String> = get_default_route_interfaces(&interfaces);
+    for interface in interfaces.iter_mut() {
+        interface.default_route = default_routes.contains(&interface.name);
+        interface.speed = get_link_speed(&interface.name);
+    }
+    interfaces
+}
+
+// getifaddrs gives one entry per address, plus one for the link itself with the MAC
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn list_interfaces() -> Vec<Interface> {
+    let Ok(addresses) = getifaddrs() else {
+        return vec![];
+    };
+    let mut interfaces: Vec<Interface> = Vec::new();
+    for address in addresses {
+        let index: usize = match interfaces
+            .iter()
+            .position(|interface| interface.name == address.interface_name)
+        {
+            Some(index) => index,
+            None => {
+                interfaces.push(new_interface(&address.interface_name));
+                interfaces.len() - 1
+            }
+        };
+        let interface: &mut Interface = &mut interfaces[index];
+        interface.up = address.flags.contains(InterfaceFlags::IFF_UP);
+        interface.loopback = address.flags.contains(InterfaceFlags::IFF_LOOPBACK);
+        add_address(interface, &address);
+    }
+    interfaces
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn add_address(interface: &mut Interface, address: &InterfaceAddress) {
+    let Some(storage) = address.address.as_ref() else {
+        return;
+    };
+    if let Some(ipv4) = storage.as_sockaddr_in() {
+        let prefix: u32 = address
+            .netmask
+            .as_ref()
+            .and_then(|netmask| netmask.as_sockaddr_in())
+            .map(|netmask| netmask.ip().count_ones())
+            .unwrap_or(32);
+        let ip: std::net::Ipv4Addr = ipv4.ip().into();
+        interface.ipv4.push(format!("{}/{}", ip, prefix));
+    } else if let Some(ipv6) = storage.as_sockaddr_in6() {
+        let prefix: u32 = address
+            .netmask
+            .as_ref()
+            .and_then(|netmask| netmask.as_sockaddr_in6())
+            .map(|netmask| u128::from(netmask.ip()).count_ones())
+            .unwrap_or(128);
+        interface.ipv6.push(format!("{}/{}", ipv6.ip(), prefix));
+    } else if let Some(link) = storage.as_link_addr() {
+        interface.mac = link
+            .addr()
+            .filter(|mac| mac.iter().any(|byte| *byte != 0))
+            .map(|mac| {
+                mac.iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<Vec<String>>()
+                    .join(":")
+            });
+    }
+}
+
+// Windows only gets the addresses, without their prefixes
+#[cfg(target_os = "windows")]
+fn list_interfaces() -> Vec<Interface> {
+    let Ok(addresses) = local_ip_address::list_afinet_netifas() else {
+        return vec![];
+    };
+    let mut interfaces: Vec<Interface> = Vec::new();
+    for (name, ip) in addresses {
+        if !interfaces.iter().any(|interface| interface.name == name) {
+            interfaces.push(new_interface(&name));
+        }
+        let interface: &mut Interface = interfaces
+            .iter_mut()
+            .find(|interface| interface.name == name)
+            .expect("Interface was just added");
+        interface.up = true;
+        interface.loopback = ip.is_loopback();
+        match ip {
+            IpAddr::V4(ip) => interface.ipv4.push(ip.to_string()),
+            IpAddr::V6(ip) => interface.ipv6.push(ip.to_string()),
+        }
+    }
+    interfaces
+}
+
+fn new_interface(name: &str) -> Interface {
+    Interface {
+        name: name.to_string(),
+        mac: None,
+        up: false,
+        loopback: false,
+        default_route: false,
+        speed: None,
+        ipv4: vec![],
+        ipv6: vec![],
+        rx_rate: None,
+        tx_rate: None,
+    }
+}
+
+// Both routing tables are read, an interface can carry only the IPv6 default route
+#[cfg(target_os = "linux")]
+fn get_default_route_interfaces(_interfaces: &[Interface]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    // Iface, Destination, Gateway, Flags, ... with the destination in hex
+    if let Ok(routes) = fs::read_to_string("/proc/net/route") {
+        for line in routes.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() > 7 && fields[1] == "00000000" && fields[7] == "00000000" {
+                names.push(fields[0].to_string());
+            }
+        }
+    }
+    // Destination, prefix length, ... with the interface last
+    if let Ok(routes) = fs::read_to_string("/proc/net/ipv6_route") {
+        for line in routes.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some(name) = fields.last() else {
+                continue;
+            };
+            if fields.len() == 10
+                && fields[0].chars().all(|c| c == '0')
+                && fields[1] == "00"
+                && *name != "lo"
+            {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+// Without a routing table to read, the interface with the address used to reach the internet
+// is the best guess
+#[cfg(not(target_os = "linux"))]
+fn get_default_route_interfaces(interfaces: &[Interface]) -> Vec<String> {
+    let Ok(ip) = local_ip_address::local_ip() else {
+        return vec![];
+    };
+    let ip: String = ip.to_string();
+    interfaces
+        .iter()
+        .filter(|interface| {
+            interface
+                .ipv4
+                .iter()
+                .chain(interface.ipv6.iter())
+                .any(|address| address.split('/').next() == Some(ip.as_str()))
+        })
+        .map(|interface| interface.name.clone())
+        .collect()
+}
+
+// In Mbps, virtual interfaces and ones that are down have none
+#[cfg(target_os = "linux")]
+fn get_link_speed(name: &str) -> Option<u64> {
+    fs::read_to_string(format!("/sys/class/net/{}/speed", name))
+        .ok()?
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|speed| *speed > 0)
+        .map(|speed| speed as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_link_speed(_name: &str) -> Option<u64> {
+    None
+}
+
+// The first IPv4 address of the interface with the default route, which is what most people
+// mean by their local IP
+pub(crate) fn derive_local_ip(interfaces: &[Interface]) -> Option<IpAddr> {
+    let candidates: Vec<&Interface> = interfaces
+        .iter()
+        .filter(|interface| interface.up && !interface.loopback && !interface.ipv4.is_empty())
+        .collect();
+    let interface: &Interface = candidates
+        .iter()
+        .find(|interface| interface.default_route)
+        .or(candidates.first())?;
+    interface.ipv4.first()?.split('/').next()?.parse().ok()
+}
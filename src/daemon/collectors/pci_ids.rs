@@ -0,0 +1,80 @@
+use std::fs;
+
+// Where distributions tend to put the database, the first one that exists wins
+const PCI_IDS_LOCATIONS: &[&str] = &[
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+    "/usr/local/share/hwdata/pci.ids",
+    "/run/current-system/sw/share/hwdata/pci.ids",
+];
+
+#[derive(Default, Debug)]
+pub(crate) struct PciNames {
+    pub(crate) vendor: Option<String>,
+    pub(crate) device: Option<String>,
+    pub(crate) subsystem: Option<String>,
+}
+
+pub(crate) fn load_pci_ids() -> Option<String> {
+    PCI_IDS_LOCATIONS
+        .iter()
+        .find_map(|location| fs::read_to_string(location).ok())
+}
+
+// The database is a list of vendors, with their devices indented by a tab under them, and each
+// device's subsystems indented by two, so only one pass is needed to find a device
+pub(crate) fn lookup_pci_names(
+    database: &str,
+    vendor_id: u16,
+    device_id: u16,
+    subsystem_ids: Option<(u16, u16)>,
+) -> PciNames {
+    let mut names: PciNames = PciNames::default();
+    let mut in_vendor: bool = false;
+    let mut in_device: bool = false;
+
+    for line in database.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(subsystem) = line.strip_prefix("\t\t") {
+            let Some((ids, name)) = subsystem.split_once("  ") else {
+                continue;
+            };
+            let Some((subsystem_vendor, subsystem_device)) = ids.split_once(' ') else {
+                continue;
+            };
+            let ids: Option<(u16, u16)> =
+                parse_id(subsystem_vendor).zip(parse_id(subsystem_device));
+            if in_device && ids.is_some() && ids == subsystem_ids {
+                names.subsystem = Some(name.to_string());
+            }
+        } else if let Some(device) = line.strip_prefix('\t') {
+            let Some((id, name)) = device.split_once("  ") else {
+                continue;
+            };
+            in_device = in_vendor && parse_id(id) == Some(device_id);
+            if in_device {
+                names.device = Some(name.to_string());
+            }
+        } else {
+            // The device classes come after the vendors, and a new vendor means ours is done
+            if in_vendor || line.starts_with("C ") {
+                break;
+            }
+            let Some((id, name)) = line.split_once("  ") else {
+                continue;
+            };
+            in_vendor = parse_id(id) == Some(vendor_id);
+            if in_vendor {
+                names.vendor = Some(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+pub(crate) fn parse_id(id: &str) -> Option<u16> {
+    u16::from_str_radix(id.trim().trim_start_matches("0x"), 16).ok()
+}
@@ -0,0 +1,273 @@
+use crate::config::daemon::{PluginOverride, Plugins, DAEMON_CONFIG_OBJECT};
+use crate::daemon::collectors::main::{error_value, Collector, Refresh};
+use serde_yaml::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::time::Duration;
+use tokio::process::Command;
+
+// Runs an executable from the plugin directory and puts whatever table it prints, as JSON or
+// TOML, into the snapshot under `custom.<plugin>`
+pub(crate) struct PluginCollector {
+    name: String,
+    path: PathBuf,
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl Collector for PluginCollector {
+    type Output = Value;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(self.interval)
+    }
+
+    async fn collect(&self) -> Value {
+        // A broken plugin shouldn't take the rest down with it, the error ends up in its own table
+        run_plugin(&self.path, self.timeout)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Plugin {} failed: {}", self.path.display(), e);
+                error_value(e)
+            })
+    }
+}
+
+pub(crate) fn discover_plugins() -> Vec<PluginCollector> {
+    read_plugins(&DAEMON_CONFIG_OBJECT.plugins)
+}
+
+fn read_plugins(config: &Plugins) -> Vec<PluginCollector> {
+    let entries: fs::ReadDir = match fs::read_dir(&config.directory) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut plugins: Vec<PluginCollector> = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let path: PathBuf = entry.path();
+        if !is_executable(&path) {
+            continue;
+        }
+        let Some(stem) = path.file_stem().map(|stem| stem.to_string_lossy()) else {
+            continue;
+        };
+        // Dots would nest the plugin one level deeper than the rest
+        let plugin_name: String = stem.replace('.', "_");
+        let plugin_override: Option<&PluginOverride> = config.overrides.get(&plugin_name);
+        let interval_seconds: u64 = plugin_override
+            .and_then(|plugin_override| plugin_override.interval_seconds)
+            .unwrap_or(config.interval_seconds);
+        let timeout_seconds: u64 = plugin_override
+            .and_then(|plugin_override| plugin_override.timeout_seconds)
+            .unwrap_or(config.timeout_seconds);
+        plugins.push(PluginCollector {
+            name: format!("custom.{}", plugin_name),
+            path,
+            interval: Duration::from_secs(interval_seconds),
+            timeout: Duration::from_secs(timeout_seconds),
+        });
+    }
+    // Plugins like `disk.sh` and `disk.py` share a name, sorting by path settles which one is kept
+    plugins.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    plugins
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+async fn run_plugin(path: &Path, timeout: Duration) -> Result<Value, String> {
+    let output: Output =
+        tokio::time::timeout(timeout, Command::new(path).kill_on_drop(true).output())
+            .await
+            .map_err(|_| format!("timed out after {:?}", timeout))?
+            .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status));
+    }
+    let stdout: String = String::from_utf8(output.stdout).map_err(|e| e.to_string())?;
+    parse_plugin_output(&stdout)
+}
+
+// TOML goes first, it's strict enough that JSON never parses as TOML, the other way around
+// any TOML would parse as a plain YAML string
+fn parse_plugin_output(stdout: &str) -> Result<Value, String> {
+    let value: Value = match toml::from_str::<toml::Table>(stdout) {
+        Ok(table) => serde_yaml::to_value(table).map_err(|e| e.to_string())?,
+        Err(_) => serde_yaml::from_str(stdout)
+            .map_err(|_| "output is neither a JSON object nor a TOML table".to_string())?,
+    };
+    match value {
+        Value::Mapping(_) => Ok(value),
+        _ => Err("output is neither a JSON object nor a TOML table".to_string()),
+    }
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+    use std::collections::BTreeMap;
+    use std::os::unix::fs::PermissionsExt;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn script(fixture: &Fixture, name: &str, body: &str) -> PathBuf {
+        let path: PathBuf = fixture.write(name, format!("#!/bin/sh\n{body}\n"));
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_json_and_toml() {
+        let fixture: Fixture = Fixture::new();
+        let json: PathBuf = script(
+            &fixture,
+            "owner.sh",
+            r#"echo '{"team": "infra", "on_call": ["kim", "alex"], "tier": 2}'"#,
+        );
+        assert_eq!(
+            run_plugin(&json, TIMEOUT).await,
+            Ok(yaml("{team: infra, on_call: [kim, alex], tier: 2}"))
+        );
+
+        let toml: PathBuf = script(
+            &fixture,
+            "asset.sh",
+            "printf 'tag = \"A-1042\"\\n[location]\\nrack = 12\\n'",
+        );
+        assert_eq!(
+            run_plugin(&toml, TIMEOUT).await,
+            Ok(yaml("{tag: A-1042, location: {rack: 12}}"))
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_output_that_isnt_a_table() {
+        let fixture: Fixture = Fixture::new();
+        for (name, output) in [
+            ("text.sh", "echo hello"),
+            ("list.sh", "echo '[1, 2, 3]'"),
+            ("number.sh", "echo 42"),
+            ("broken.sh", "echo '{\"team\": '"),
+        ] {
+            let path: PathBuf = script(&fixture, name, output);
+            assert_eq!(
+                run_plugin(&path, TIMEOUT).await,
+                Err("output is neither a JSON object nor a TOML table".to_string()),
+                "{name}"
+            );
+        }
+        // No output at all is an empty TOML document, a plugin with nothing to report
+        let path: PathBuf = script(&fixture, "nothing.sh", "true");
+        assert_eq!(run_plugin(&path, TIMEOUT).await, Ok(yaml("{}")));
+    }
+
+    #[tokio::test]
+    async fn reports_a_failing_plugin() {
+        let fixture: Fixture = Fixture::new();
+        let path: PathBuf = script(&fixture, "fails.sh", "echo '{\"a\": 1}'; exit 3");
+        assert_eq!(
+            run_plugin(&path, TIMEOUT).await,
+            Err("exited with exit status: 3".to_string())
+        );
+
+        // The error goes into the plugin's own table
+        let collector: PluginCollector = PluginCollector {
+            name: "custom.fails".to_string(),
+            path,
+            interval: TIMEOUT,
+            timeout: TIMEOUT,
+        };
+        assert_eq!(
+            collector.collect().await,
+            yaml("error: 'exited with exit status: 3'")
+        );
+    }
+
+    #[tokio::test]
+    async fn times_out() {
+        let fixture: Fixture = Fixture::new();
+        let path: PathBuf = script(&fixture, "hangs.sh", "sleep 30");
+        let timeout: Duration = Duration::from_millis(200);
+        let started: std::time::Instant = std::time::Instant::now();
+        assert_eq!(
+            run_plugin(&path, timeout).await,
+            Err("timed out after 200ms".to_string())
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn finds_executables_in_the_directory() {
+        let fixture: Fixture = Fixture::new();
+        script(&fixture, "plugins/owner.sh", "true");
+        script(&fixture, "plugins/disk.sh", "true");
+        script(&fixture, "plugins/disk.py", "true");
+        script(&fixture, "plugins/asset.tag.sh", "true");
+        fixture.write("plugins/README.md", "Not executable");
+        fixture.create_dir("plugins/lib");
+
+        let mut overrides: BTreeMap<String, PluginOverride> = BTreeMap::new();
+        overrides.insert(
+            "owner".to_string(),
+            PluginOverride {
+                interval_seconds: Some(3600),
+                timeout_seconds: None,
+            },
+        );
+        let config: Plugins = Plugins {
+            directory: fixture.path().join("plugins").to_string_lossy().to_string(),
+            interval_seconds: 60,
+            timeout_seconds: 10,
+            overrides,
+        };
+        let plugins: Vec<PluginCollector> = read_plugins(&config);
+        let found: Vec<(&str, &str, u64, u64)> = plugins
+            .iter()
+            .map(|plugin| {
+                (
+                    plugin.name.as_str(),
+                    plugin.path.file_name().unwrap().to_str().unwrap(),
+                    plugin.interval.as_secs(),
+                    plugin.timeout.as_secs(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("custom.asset_tag", "asset.tag.sh", 60, 10),
+                ("custom.disk", "disk.py", 60, 10),
+                ("custom.disk", "disk.sh", 60, 10),
+                ("custom.owner", "owner.sh", 3600, 10),
+            ]
+        );
+
+        let missing: Plugins = Plugins {
+            directory: fixture.path().join("missing").to_string_lossy().to_string(),
+            ..config
+        };
+        assert!(read_plugins(&missing).is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::sysfs::{read_number, read_trimmed};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Battery {
+    pub(crate) name: String,
+    pub(crate) manufacturer: Option<String>,
+    pub(crate) model: Option<String>,
+    pub(crate) technology: Option<String>,
+    pub(crate) status: String,
+    pub(crate) capacity: Option<u64>,
+    pub(crate) health: Option<u64>,
+    pub(crate) cycle_count: Option<u64>,
+    pub(crate) time_remaining: Option<u64>,
+    pub(crate) power: Option<f64>,
+}
+
+pub(crate) struct BatteriesCollector;
+
+impl Collector for BatteriesCollector {
+    type Output = Vec<Battery>;
+
+    fn name(&self) -> &str {
+        "batteries"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(10))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &["status", "capacity", "time_remaining", "power"]
+    }
+
+    async fn collect(&self) -> Vec<Battery> {
+        get_batteries()
+    }
+}
+
+// Whether the system runs off an AC adapter, unknown on systems without one like most desktops
+pub(crate) struct AcPowerCollector;
+
+impl Collector for AcPowerCollector {
+    type Output = Option<bool>;
+
+    fn name(&self) -> &str {
+        "ac_power"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(10))
+    }
+
+    async fn collect(&self) -> Option<bool> {
+        get_ac_power()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_batteries() -> Vec<Battery> {
+    read_batteries(Path::new("/sys"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_batteries() -> Vec<Battery> {
+    vec![]
+}
+
+#[cfg(target_os = "linux")]
+fn read_batteries(sys_root: &Path) -> Vec<Battery> {
+    let mut batteries: Vec<Battery> = power_supplies(sys_root, "Battery")
+        .iter()
+        // Mice, keyboards and headsets report their batteries here as well
+        .filter(|path| read_trimmed(path.join("scope")).as_deref() != Some("Device"))
+        .filter_map(|path| read_battery(path))
+        .collect();
+    batteries.sort_by(|a, b| a.name.cmp(&b.name));
+    batteries
+}
+
+#[cfg(target_os = "linux")]
+fn get_ac_power() -> Option<bool> {
+    read_ac_power(Path::new("/sys"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_ac_power() -> Option<bool> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_ac_power(sys_root: &Path) -> Option<bool> {
+    let adapters: Vec<bool> = power_supplies(sys_root, "Mains")
+        .iter()
+        .filter_map(|path| read_number(path.join("online")))
+        .map(|online| online == 1)
+        .collect();
+    match adapters.is_empty() {
+        true => None,
+        false => Some(adapters.contains(&true)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn power_supplies(sys_root: &Path, kind: &str) -> Vec<PathBuf> {
+    match fs::read_dir(sys_root.join("class/power_supply")) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| read_trimmed(path.join("type")).as_deref() == Some(kind))
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+// Batteries report either energy in µWh and power in µW, or charge in µAh and current in µA,
+// the math for the time remaining works out the same either way
+#[cfg(target_os = "linux")]
+fn read_battery(path: &Path) -> Option<Battery> {
+    let name: String = path.file_name()?.to_string_lossy().to_string();
+    let (now, full, design, rate): (Option<u64>, Option<u64>, Option<u64>, Option<u64>) =
+        match path.join("energy_now").exists() {
+            true => (
+                read_number(path.join("energy_now")),
+                read_number(path.join("energy_full")),
+                read_number(path.join("energy_full_design")),
+                read_number(path.join("power_now")),
+            ),
+            false => (
+                read_number(path.join("charge_now")),
+                read_number(path.join("charge_full")),
+                read_number(path.join("charge_full_design")),
+                read_number(path.join("current_now")),
+            ),
+        };
+    let status: String = read_trimmed(path.join("status")).unwrap_or("Unknown".to_string());
+
+    let capacity: Option<u64> = read_number(path.join("capacity")).or_else(|| {
+        let (now, full) = now.zip(full).filter(|(_, full)| *full > 0)?;
+        Some(now * 100 / full)
+    });
+    let health: Option<u64> = full
+        .zip(design)
+        .filter(|(_, design)| *design > 0)
+        .map(|(full, design)| full * 100 / design);
+    // Plenty of batteries don't count their cycles and report 0
+    let cycle_count: Option<u64> =
+        read_number(path.join("cycle_count")).filter(|cycles| *cycles > 0);
+
+    let rate: Option<u64> = rate.filter(|rate| *rate > 0);
+    let time_remaining: Option<u64> = match status.as_str() {
+        "Discharging" => now.zip(rate).map(|(now, rate)| now * 3600 / rate),
+        "Charging" => now
+            .zip(full)
+            .zip(rate)
+            .map(|((now, full), rate)| full.saturating_sub(now) * 3600 / rate),
+        _ => None,
+    };
+    // Some charge based batteries have `power_now` too, only those without it need the power
+    // worked out from the voltage and current
+    let power: Option<f64> = match read_number(path.join("power_now")) {
+        Some(power) => Some(power as f64 / 1_000_000.0),
+        None => read_number(path.join("voltage_now"))
+            .zip(read_number(path.join("current_now")))
+            .map(|(voltage, current)| voltage as f64 * current as f64 / 1_000_000_000_000.0),
+    }
+    .filter(|power| *power > 0.0);
+
+    Some(Battery {
+        name,
+        manufacturer: read_trimmed(path.join("manufacturer")),
+        model: read_trimmed(path.join("model_name")),
+        technology: read_trimmed(path.join("technology")),
+        status,
+        capacity,
+        health,
+        cycle_count,
+        time_remaining,
+        power,
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    // Writes a power supply's attributes, one file each
+    fn supply(fixture: &Fixture, name: &str, attributes: &[(&str, &str)]) {
+        for (attribute, value) in attributes {
+            fixture.write(
+                &format!("class/power_supply/{name}/{attribute}"),
+                format!("{value}\n"),
+            );
+        }
+    }
+
+    #[test]
+    fn reads_an_energy_based_battery() {
+        let fixture: Fixture = Fixture::new();
+        supply(
+            &fixture,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("manufacturer", "SMP"),
+                ("model_name", "5B10W13930"),
+                ("technology", "Li-poly"),
+                ("capacity", "60"),
+                ("energy_now", "30000000"),
+                ("energy_full", "50000000"),
+                ("energy_full_design", "57000000"),
+                ("power_now", "10000000"),
+                ("voltage_now", "11400000"),
+                ("cycle_count", "245"),
+            ],
+        );
+        supply(&fixture, "AC", &[("type", "Mains"), ("online", "0")]);
+        // A wireless mouse, which isn't the system's battery
+        supply(
+            &fixture,
+            "hidpp_battery_0",
+            &[("type", "Battery"), ("scope", "Device"), ("capacity", "90")],
+        );
+
+        let batteries: Vec<Battery> = read_batteries(fixture.path());
+        assert_eq!(batteries.len(), 1);
+        let battery: &Battery = &batteries[0];
+        assert_eq!(battery.name, "BAT0");
+        assert_eq!(battery.manufacturer.as_deref(), Some("SMP"));
+        assert_eq!(battery.model.as_deref(), Some("5B10W13930"));
+        assert_eq!(battery.technology.as_deref(), Some("Li-poly"));
+        assert_eq!(battery.status, "Discharging");
+        assert_eq!(battery.capacity, Some(60));
+        assert_eq!(battery.health, Some(87));
+        assert_eq!(battery.cycle_count, Some(245));
+        // 30 Wh left at 10 W
+        assert_eq!(battery.time_remaining, Some(3 * 3600));
+        assert_eq!(battery.power, Some(10.0));
+        assert_eq!(read_ac_power(fixture.path()), Some(false));
+    }
+
+    #[test]
+    fn reads_charge_based_batteries() {
+        let fixture: Fixture = Fixture::new();
+        let charge: [(&str, &str); 8] = [
+            ("type", "Battery"),
+            ("status", "Charging"),
+            ("charge_now", "2000000"),
+            ("charge_full", "4000000"),
+            ("charge_full_design", "4400000"),
+            ("current_now", "1000000"),
+            ("voltage_now", "12000000"),
+            ("cycle_count", "0"),
+        ];
+        supply(&fixture, "BAT0", &charge);
+        // The same, but with the power reported as well, which isn't the current
+        supply(&fixture, "BAT1", &charge);
+        supply(&fixture, "BAT1", &[("power_now", "15000000")]);
+        supply(&fixture, "ADP1", &[("type", "Mains"), ("online", "1")]);
+
+        let batteries: Vec<Battery> = read_batteries(fixture.path());
+        let names: Vec<&str> = batteries
+            .iter()
+            .map(|battery| battery.name.as_str())
+            .collect();
+        assert_eq!(names, ["BAT0", "BAT1"]);
+        for battery in &batteries {
+            // Worked out from the charge, there's no capacity file
+            assert_eq!(battery.capacity, Some(50));
+            assert_eq!(battery.health, Some(90));
+            // Batteries that don't count their cycles report 0
+            assert_eq!(battery.cycle_count, None);
+            // 2 Ah to go at 1 A
+            assert_eq!(battery.time_remaining, Some(2 * 3600));
+        }
+        // 12 V at 1 A
+        assert_eq!(batteries[0].power, Some(12.0));
+        assert_eq!(batteries[1].power, Some(15.0));
+        assert_eq!(read_ac_power(fixture.path()), Some(true));
+    }
+
+    #[test]
+    fn reads_a_full_battery() {
+        let fixture: Fixture = Fixture::new();
+        supply(
+            &fixture,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Full"),
+                ("capacity", "100"),
+                ("energy_now", "50000000"),
+                ("energy_full", "50000000"),
+                ("power_now", "0"),
+            ],
+        );
+        let battery: &Battery = &read_batteries(fixture.path())[0];
+        assert_eq!(battery.status, "Full");
+        assert_eq!(battery.health, None);
+        assert_eq!(battery.time_remaining, None);
+        assert_eq!(battery.power, None);
+    }
+
+    #[test]
+    fn reads_a_desktop() {
+        let fixture: Fixture = Fixture::new();
+        assert!(read_batteries(fixture.path()).is_empty());
+        assert_eq!(read_ac_power(fixture.path()), None);
+
+        // USB-C ports show up as power supplies too
+        supply(
+            &fixture,
+            "ucsi-source-psy-USBC000:001",
+            &[("type", "USB"), ("online", "0")],
+        );
+        assert!(read_batteries(fixture.path()).is_empty());
+        assert_eq!(read_ac_power(fixture.path()), None);
+    }
+}
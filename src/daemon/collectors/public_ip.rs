@@ -0,0 +1,251 @@
+use crate::config::daemon::{PublicIp as PublicIpConfig, DAEMON_CONFIG_OBJECT};
+use crate::daemon::collectors::main::{Collector, Refresh};
+use lazy_static::lazy_static;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, MutexGuard};
+
+lazy_static! {
+    // Both collectors report the same lookup, whichever runs first does it while the other waits
+    static ref LOOKUP: Mutex<Option<(Instant, PublicIp)>> = Mutex::new(None);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct PublicIp {
+    pub(crate) ipv4: Option<String>,
+    pub(crate) ipv6: Option<String>,
+}
+
+// Only registered when the lookup is enabled in the daemon config
+pub(crate) struct PublicIpCollector;
+
+impl Collector for PublicIpCollector {
+    type Output = PublicIp;
+
+    fn name(&self) -> &str {
+        "public_ip_info"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(
+            DAEMON_CONFIG_OBJECT.public_ip.interval_seconds,
+        ))
+    }
+
+    async fn collect(&self) -> PublicIp {
+        cached_lookup(&DAEMON_CONFIG_OBJECT.public_ip).await
+    }
+}
+
+// `public_ip` was always a string before the lookup became opt-in, configs written back then
+// still concatenate it. It's the IPv4 address, or the IPv6 one when there's no IPv4
+pub(crate) struct PublicIpAddressCollector;
+
+impl Collector for PublicIpAddressCollector {
+    type Output = String;
+
+    fn name(&self) -> &str {
+        "public_ip"
+    }
+
+    fn refresh(&self) -> Refresh {
+        let config: &PublicIpConfig = &DAEMON_CONFIG_OBJECT.public_ip;
+        match config.enabled {
+            true => Refresh::Every(Duration::from_secs(config.interval_seconds)),
+            false => Refresh::Once,
+        }
+    }
+
+    async fn collect(&self) -> String {
+        let config: &PublicIpConfig = &DAEMON_CONFIG_OBJECT.public_ip;
+        if !config.enabled {
+            return "Disabled".to_string();
+        }
+        let public_ip: PublicIp = cached_lookup(config).await;
+        public_ip
+            .ipv4
+            .or(public_ip.ipv6)
+            .unwrap_or("Unknown".to_string())
+    }
+}
+
+// The two collectors refresh on the same interval, so a lookup from the first half of it is the
+// one the other collector just did
+async fn cached_lookup(config: &PublicIpConfig) -> PublicIp {
+    let mut lookup: MutexGuard<Option<(Instant, PublicIp)>> = LOOKUP.lock().await;
+    let fresh_for: Duration = Duration::from_secs(config.interval_seconds) / 2;
+    if let Some((_, public_ip)) = lookup.as_ref().filter(|(at, _)| at.elapsed() < fresh_for) {
+        return public_ip.clone();
+    }
+    let started: Instant = Instant::now();
+    let timeout: Duration = Duration::from_secs(config.timeout_seconds);
+    let ipv4: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    let ipv6: IpAddr = IpAddr::V6(Ipv6Addr::UNSPECIFIED);
+    let public_ip: PublicIp = PublicIp {
+        ipv4: lookup_public_ip(&build_client(timeout, ipv4), &config.ipv4_endpoints, ipv4).await,
+        ipv6: lookup_public_ip(&build_client(timeout, ipv6), &config.ipv6_endpoints, ipv6).await,
+    };
+    *lookup = Some((started, public_ip.clone()));
+    public_ip
+}
+
+// Binding to the unspecified address of one family keeps the request on that family, even when
+// an endpoint has addresses of both
+fn build_client(timeout: Duration, local_address: IpAddr) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .local_address(local_address)
+        .build()
+        .expect("Failed to build reqwest client")
+}
+
+// The first endpoint that answers with an address of the same family as `local_address` wins
+async fn lookup_public_ip(
+    client: &Client,
+    endpoints: &[String],
+    local_address: IpAddr,
+) -> Option<String> {
+    for endpoint in endpoints {
+        let body: String = match client.get(endpoint).send().await {
+            Ok(response) if response.status().is_success() => match response.text().await {
+                Ok(body) => body,
+                Err(_) => continue,
+            },
+            _ => continue,
+        };
+        let address: Option<IpAddr> = body.trim().parse().ok();
+        match address {
+            Some(address) if address.is_ipv4() == local_address.is_ipv4() => {
+                return Some(address.to_string())
+            }
+            _ => eprintln!(
+                "Public IP endpoint {} did not return an IP address",
+                endpoint
+            ),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    // Answers every request with the canned status and body for its path, and remembers the
+    // paths in the order they were asked for
+    async fn serve(
+        routes: &'static [(&'static str, u16, &'static str)],
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base: String = format!("http://{}", listener.local_addr().unwrap());
+        let requested: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+        let log: Arc<Mutex<Vec<String>>> = requested.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _): (TcpStream, _) = listener.accept().await.unwrap();
+                let mut request: Vec<u8> = vec![0; 4096];
+                let length: usize = stream.read(&mut request).await.unwrap();
+                let request: String = String::from_utf8_lossy(&request[..length]).to_string();
+                let path: String = request
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                log.lock().unwrap().push(path.clone());
+                let (status, body): (u16, &str) = routes
+                    .iter()
+                    .find(|(route, _, _)| *route == path)
+                    .map(|(_, status, body)| (*status, *body))
+                    .unwrap_or((404, ""));
+                let response: String = format!(
+                    "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (base, requested)
+    }
+
+    // Without a proxy, in case the environment sets one that can't reach the loopback address
+    fn test_client() -> Client {
+        Client::builder()
+            .timeout(Duration::from_secs(5))
+            .no_proxy()
+            .build()
+            .unwrap()
+    }
+
+    fn endpoints(base: &str, paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|path| format!("{base}{path}")).collect()
+    }
+
+    #[tokio::test]
+    async fn falls_back_in_order_until_an_address() {
+        let (base, requested) = serve(&[
+            ("/error", 503, "198.51.100.1"),
+            ("/html", 200, "<html><body>198.51.100.2</body></html>"),
+            ("/empty", 200, ""),
+            ("/ipv4", 200, "  203.0.113.7\n"),
+            ("/unused", 200, "198.51.100.3"),
+        ])
+        .await;
+        let paths: &[&str] = &["/error", "/html", "/missing", "/empty", "/ipv4", "/unused"];
+        let address: Option<String> = lookup_public_ip(
+            &test_client(),
+            &endpoints(&base, paths),
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        )
+        .await;
+        assert_eq!(address.as_deref(), Some("203.0.113.7"));
+        assert_eq!(*requested.lock().unwrap(), paths[..5]);
+    }
+
+    #[tokio::test]
+    async fn rejects_addresses_of_the_other_family() {
+        let (base, _) = serve(&[
+            ("/ipv4", 200, "203.0.113.7"),
+            ("/ipv6", 200, "2001:db8::7\n"),
+        ])
+        .await;
+        let client: Client = test_client();
+        let ipv6: Option<String> = lookup_public_ip(
+            &client,
+            &endpoints(&base, &["/ipv4", "/ipv6"]),
+            IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        )
+        .await;
+        assert_eq!(ipv6.as_deref(), Some("2001:db8::7"));
+        let ipv4: Option<String> = lookup_public_ip(
+            &client,
+            &endpoints(&base, &["/ipv6"]),
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        )
+        .await;
+        assert_eq!(ipv4, None);
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_no_endpoint_answers() {
+        // Nothing listens on the port once the listener is dropped
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base: String = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let address: Option<String> = lookup_public_ip(
+            &test_client(),
+            &endpoints(&base, &["/ipv4"]),
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        )
+        .await;
+        assert_eq!(address, None);
+        assert_eq!(
+            lookup_public_ip(&test_client(), &[], IpAddr::V4(Ipv4Addr::UNSPECIFIED)).await,
+            None
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::sysfs::read_trimmed;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Which chip and label hold the temperature people mean by "the CPU temperature", the first
+// one present wins
+#[cfg(target_os = "linux")]
+const CPU_SENSORS: &[(&str, &str)] = &[
+    ("coretemp", "Package id 0"),
+    ("k10temp", "Tdie"),
+    ("k10temp", "Tctl"),
+    ("zenpower", "Tdie"),
+    ("cpu_thermal", "temp1"),
+    ("soc_thermal", "temp1"),
+];
+#[cfg(target_os = "linux")]
+const GPU_SENSORS: &[(&str, &str)] = &[
+    ("amdgpu", "edge"),
+    ("nouveau", "temp1"),
+    ("i915", "temp1"),
+    ("xe", "temp1"),
+];
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Sensors {
+    pub(crate) cpu: Option<f64>,
+    pub(crate) gpu: Option<f64>,
+    pub(crate) chips: Vec<Chip>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Chip {
+    pub(crate) name: String,
+    pub(crate) id: String,
+    pub(crate) temperatures: Vec<Sensor>,
+    pub(crate) fans: Vec<Sensor>,
+    pub(crate) voltages: Vec<Sensor>,
+}
+
+// Temperatures are in degrees Celsius, fans in RPM and voltages in volts
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Sensor {
+    pub(crate) name: String,
+    pub(crate) input: f64,
+    pub(crate) max: Option<f64>,
+    pub(crate) crit: Option<f64>,
+}
+
+pub(crate) struct SensorsCollector;
+
+impl Collector for SensorsCollector {
+    type Output = Sensors;
+
+    fn name(&self) -> &str {
+        "sensors"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(2))
+    }
+
+    fn volatile_fields(&self) -> &[&str] {
+        &[
+            "cpu",
+            "gpu",
+            "chips.temperatures.input",
+            "chips.fans.input",
+            "chips.voltages.input",
+        ]
+    }
+
+    async fn collect(&self) -> Sensors {
+        get_sensors()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_sensors() -> Sensors {
+    read_sensors(Path::new("/sys"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_sensors() -> Sensors {
+    Sensors::default()
+}
+
+#[cfg(target_os = "linux")]
+fn read_sensors(sys_root: &Path) -> Sensors {
+    let mut chips: Vec<(u32, Chip)> = match fs::read_dir(sys_root.join("class/hwmon")) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let id: String = entry.file_name().to_string_lossy().to_string();
+                let number: u32 = id.strip_prefix("hwmon")?.parse().ok()?;
+                Some((number, read_chip(&entry.path(), id)?))
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    chips.sort_by_key(|(number, _)| *number);
+    let chips: Vec<Chip> = chips.into_iter().map(|(_, chip)| chip).collect();
+
+    Sensors {
+        cpu: pick_temperature(&chips, CPU_SENSORS),
+        gpu: pick_temperature(&chips, GPU_SENSORS),
+        chips,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_chip(path: &Path, id: String) -> Option<Chip> {
+    let name: String = read_trimmed(path.join("name"))?;
+    Some(Chip {
+        name,
+        id,
+        // Millidegrees Celsius and millivolts, fans are already in RPM
+        temperatures: read_inputs(path, "temp", 1000.0),
+        fans: read_inputs(path, "fan", 1.0),
+        voltages: read_inputs(path, "in", 1000.0),
+    })
+}
+
+// Every input is a `<kind><n>_input` file, with the optional label and thresholds next to it
+#[cfg(target_os = "linux")]
+fn read_inputs(path: &Path, kind: &str, divisor: f64) -> Vec<Sensor> {
+    let Ok(entries) = fs::read_dir(path) else {
+        return vec![];
+    };
+    let mut numbers: Vec<u32> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name: String = entry.file_name().to_string_lossy().to_string();
+            file_name
+                .strip_prefix(kind)?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    numbers.sort();
+
+    numbers
+        .into_iter()
+        .filter_map(|number| {
+            let prefix: String = format!("{}{}", kind, number);
+            let read_value = |suffix: &str| -> Option<f64> {
+                let file: PathBuf = path.join(format!("{}_{}", prefix, suffix));
+                Some(read_trimmed(&file)?.parse::<f64>().ok()? / divisor)
+            };
+            // Sensors that aren't wired up fail to read, they're left out
+            let input: f64 = read_value("input")?;
+            let max: Option<f64> = read_value("max");
+            let crit: Option<f64> = read_value("crit");
+            Some(Sensor {
+                name: read_trimmed(path.join(format!("{}_label", prefix))).unwrap_or(prefix),
+                input,
+                max,
+                crit,
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn pick_temperature(chips: &[Chip], candidates: &[(&str, &str)]) -> Option<f64> {
+    candidates.iter().find_map(|(chip_name, label)| {
+        chips
+            .iter()
+            .filter(|chip| chip.name == *chip_name)
+            .flat_map(|chip| chip.temperatures.iter())
+            .find(|sensor| sensor.name == *label)
+            .map(|sensor| sensor.input)
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    // Writes `<kind><n>_input` and the files next to it, `None` leaves one out
+    fn write_input(
+        fixture: &Fixture,
+        chip: &str,
+        prefix: &str,
+        label: Option<&str>,
+        input: &str,
+        crit: Option<&str>,
+    ) {
+        fixture.write(&format!("class/hwmon/{chip}/{prefix}_input"), input);
+        if let Some(label) = label {
+            fixture.write(&format!("class/hwmon/{chip}/{prefix}_label"), label);
+        }
+        if let Some(crit) = crit {
+            fixture.write(&format!("class/hwmon/{chip}/{prefix}_crit"), crit);
+        }
+    }
+
+    #[test]
+    fn reads_an_amd_desktop() {
+        let fixture: Fixture = Fixture::new();
+        fixture.write("class/hwmon/hwmon0/name", "nvme\n");
+        write_input(
+            &fixture,
+            "hwmon0",
+            "temp1",
+            Some("Composite\n"),
+            "38850\n",
+            None,
+        );
+        fixture.write("class/hwmon/hwmon2/name", "k10temp\n");
+        write_input(&fixture, "hwmon2", "temp1", Some("Tctl\n"), "65500\n", None);
+        write_input(
+            &fixture,
+            "hwmon2",
+            "temp3",
+            Some("Tccd1\n"),
+            "58250\n",
+            None,
+        );
+        // Numbered past 9, so sorting by name would put it before hwmon2
+        fixture.write("class/hwmon/hwmon10/name", "amdgpu\n");
+        write_input(
+            &fixture,
+            "hwmon10",
+            "temp1",
+            Some("edge\n"),
+            "52000\n",
+            Some("100000\n"),
+        );
+        write_input(
+            &fixture,
+            "hwmon10",
+            "temp2",
+            Some("junction\n"),
+            "56000\n",
+            Some("110000\n"),
+        );
+        write_input(&fixture, "hwmon10", "fan1", None, "1180\n", None);
+        write_input(&fixture, "hwmon10", "in0", Some("vddgfx\n"), "825\n", None);
+        fixture.write("class/hwmon/hwmon10/fan1_max", "3300\n");
+
+        let sensors: Sensors = read_sensors(fixture.path());
+        assert_eq!(sensors.cpu, Some(65.5));
+        assert_eq!(sensors.gpu, Some(52.0));
+        let names: Vec<&str> = sensors
+            .chips
+            .iter()
+            .map(|chip| chip.name.as_str())
+            .collect();
+        assert_eq!(names, ["nvme", "k10temp", "amdgpu"]);
+
+        let k10temp: &Chip = &sensors.chips[1];
+        assert_eq!(k10temp.id, "hwmon2");
+        let labels: Vec<&str> = k10temp
+            .temperatures
+            .iter()
+            .map(|sensor| sensor.name.as_str())
+            .collect();
+        assert_eq!(labels, ["Tctl", "Tccd1"]);
+
+        let amdgpu: &Chip = &sensors.chips[2];
+        assert_eq!(amdgpu.temperatures[1].name, "junction");
+        assert_eq!(amdgpu.temperatures[1].crit, Some(110.0));
+        assert_eq!(amdgpu.temperatures[1].max, None);
+        // Fans are in RPM already and unlabeled inputs go by their file name
+        assert_eq!(amdgpu.fans[0].name, "fan1");
+        assert_eq!(amdgpu.fans[0].input, 1180.0);
+        assert_eq!(amdgpu.fans[0].max, Some(3300.0));
+        assert_eq!(amdgpu.voltages[0].name, "vddgfx");
+        assert_eq!(amdgpu.voltages[0].input, 0.825);
+    }
+
+    #[test]
+    fn reads_an_intel_laptop() {
+        let fixture: Fixture = Fixture::new();
+        fixture.write("class/hwmon/hwmon0/name", "acpitz\n");
+        write_input(
+            &fixture,
+            "hwmon0",
+            "temp1",
+            None,
+            "27800\n",
+            Some("119000\n"),
+        );
+        fixture.write("class/hwmon/hwmon4/name", "coretemp\n");
+        write_input(
+            &fixture,
+            "hwmon4",
+            "temp2",
+            Some("Core 0\n"),
+            "44000\n",
+            None,
+        );
+        write_input(
+            &fixture,
+            "hwmon4",
+            "temp1",
+            Some("Package id 0\n"),
+            "47000\n",
+            None,
+        );
+        // A sensor that isn't wired up fails to read, here because its input is a directory
+        fixture.create_dir("class/hwmon/hwmon4/temp3_input");
+
+        let sensors: Sensors = read_sensors(fixture.path());
+        assert_eq!(sensors.cpu, Some(47.0));
+        assert_eq!(sensors.gpu, None);
+        let coretemp: &Chip = &sensors.chips[1];
+        let labels: Vec<&str> = coretemp
+            .temperatures
+            .iter()
+            .map(|sensor| sensor.name.as_str())
+            .collect();
+        assert_eq!(labels, ["Package id 0", "Core 0"]);
+        assert!(coretemp.fans.is_empty());
+    }
+
+    #[test]
+    fn prefers_tdie_over_tctl() {
+        // Older Ryzens offset Tctl from the real temperature and report Tdie next to it
+        let fixture: Fixture = Fixture::new();
+        fixture.write("class/hwmon/hwmon1/name", "k10temp\n");
+        write_input(&fixture, "hwmon1", "temp1", Some("Tctl\n"), "72000\n", None);
+        write_input(&fixture, "hwmon1", "temp2", Some("Tdie\n"), "52000\n", None);
+        assert_eq!(read_sensors(fixture.path()).cpu, Some(52.0));
+    }
+
+    #[test]
+    fn skips_chips_without_a_name() {
+        let fixture: Fixture = Fixture::new();
+        write_input(&fixture, "hwmon0", "temp1", None, "40000\n", None);
+        fixture.write("class/hwmon/hwmon1/name", "k10temp\n");
+        fixture.create_dir("class/hwmon/not_a_chip");
+
+        let sensors: Sensors = read_sensors(fixture.path());
+        assert_eq!(sensors.chips.len(), 1);
+        assert_eq!(sensors.cpu, None);
+    }
+
+    #[test]
+    fn reads_nothing_without_hwmon() {
+        let fixture: Fixture = Fixture::new();
+        let sensors: Sensors = read_sensors(fixture.path());
+        assert!(sensors.chips.is_empty());
+        assert_eq!((sensors.cpu, sensors.gpu), (None, None));
+    }
+}
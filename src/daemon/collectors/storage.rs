@@ -0,0 +1,699 @@
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::disks::{get_mount_groups, Mount};
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::sysfs::read_trimmed;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::collections::BTreeMap;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Device mapper targets nest, LVM on LUKS on RAID is about as deep as it gets in practice
+#[cfg(target_os = "linux")]
+const MAX_DEPTH: usize = 16;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Storage {
+    pub(crate) name: String,
+    pub(crate) device: String,
+    pub(crate) filesystem: String,
+    pub(crate) layers: Vec<Layer>,
+    pub(crate) disks: Vec<PhysicalDisk>,
+}
+
+// A layer between the filesystem and the disks, from the top down
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Layer {
+    pub(crate) kind: LayerKind,
+    pub(crate) name: String,
+    // Only for RAID, like `raid1`
+    pub(crate) level: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum LayerKind {
+    Luks,
+    Crypt,
+    Lvm,
+    Raid,
+    Multipath,
+    DeviceMapper,
+    Zfs,
+    Btrfs,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct PhysicalDisk {
+    pub(crate) name: String,
+    pub(crate) model: Option<String>,
+    pub(crate) kind: DiskKind,
+    // In bytes
+    pub(crate) size: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) enum DiskKind {
+    Nvme,
+    Ssd,
+    Hdd,
+    Virtual,
+    Unknown,
+}
+
+// Everything that's read once, while walking down from one filesystem
+#[cfg(target_os = "linux")]
+struct Topology {
+    // The devices below each device, from the holders of every device
+    lower: BTreeMap<String, Vec<String>>,
+    // The level and members of each RAID array
+    arrays: BTreeMap<String, (Option<String>, Vec<String>)>,
+}
+
+pub(crate) struct StorageCollector;
+
+impl Collector for StorageCollector {
+    type Output = Vec<Storage>;
+
+    fn name(&self) -> &str {
+        "storage"
+    }
+
+    // Storage only changes when disks get set up or plugged in
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(60))
+    }
+
+    async fn collect(&self) -> Vec<Storage> {
+        get_storage()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_storage() -> Vec<Storage> {
+    let mounts: Vec<Mount> = get_mount_groups()
+        .into_iter()
+        .filter_map(|group| group.into_iter().next())
+        .collect();
+    read_storage(Path::new("/"), &mounts)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_storage() -> Vec<Storage> {
+    vec![]
+}
+
+#[cfg(target_os = "linux")]
+fn read_storage(root: &Path, mounts: &[Mount]) -> Vec<Storage> {
+    let topology: Topology = Topology {
+        lower: read_holders(root),
+        arrays: fs::read_to_string(root.join("proc/mdstat"))
+            .map(|mdstat| parse_mdstat(&mdstat))
+            .unwrap_or_default(),
+    };
+    mounts
+        .iter()
+        .filter_map(|mount| {
+            let mut layers: Vec<Layer> = vec![];
+            let mut disks: Vec<PhysicalDisk> = vec![];
+            match mount.filesystem.as_str() {
+                // ZFS datasets are named after their pool, like `rpool/ROOT/ubuntu`
+                "zfs" => {
+                    let pool: &str = mount.device.split('/').next()?;
+                    layers.push(Layer {
+                        kind: LayerKind::Zfs,
+                        name: pool.to_string(),
+                        level: None,
+                    });
+                    for member in get_zfs_members(root, pool) {
+                        walk(root, &topology, &member, &mut layers, &mut disks, 0);
+                    }
+                }
+                _ => {
+                    let name: String = resolve_block_name(root, &mount.device)?;
+                    let members: Vec<String> = match mount.filesystem.as_str() {
+                        "btrfs" => get_btrfs_members(root, &name, &mut layers),
+                        _ => vec![name],
+                    };
+                    for member in members {
+                        walk(root, &topology, &member, &mut layers, &mut disks, 0);
+                    }
+                }
+            }
+            // Network and virtual filesystems don't have anything underneath
+            if layers.is_empty() && disks.is_empty() {
+                return None;
+            }
+            Some(Storage {
+                name: mount.mount_point.clone(),
+                device: mount.device.clone(),
+                filesystem: mount.filesystem.clone(),
+                layers,
+                disks,
+            })
+        })
+        .collect()
+}
+
+// Mount sources are usually the kernel's name, device mapper devices go by their /dev/mapper
+// name, LVM volumes sometimes by their /dev/<vg>/<lv> link
+#[cfg(target_os = "linux")]
+fn resolve_block_name(root: &Path, device: &str) -> Option<String> {
+    let name: &str = device.strip_prefix("/dev/")?;
+    let blocks: PathBuf = root.join("sys/class/block");
+    if let Some(mapper_name) = name.strip_prefix("mapper/") {
+        return fs::read_dir(&blocks)
+            .ok()?
+            .filter_map(Result::ok)
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .find(|block| {
+                read_trimmed(blocks.join(block).join("dm/name")).as_deref() == Some(mapper_name)
+            });
+    }
+    if blocks.join(name).exists() {
+        return Some(name.to_string());
+    }
+    let target: PathBuf = fs::canonicalize(root.join("dev").join(name)).ok()?;
+    let target: String = target.file_name()?.to_string_lossy().to_string();
+    match blocks.join(&target).exists() {
+        true => Some(target),
+        false => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn walk(
+    root: &Path,
+    topology: &Topology,
+    name: &str,
+    layers: &mut Vec<Layer>,
+    disks: &mut Vec<PhysicalDisk>,
+    depth: usize,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let block: PathBuf = root.join("sys/class/block").join(name);
+    let lower: Vec<String> = topology.lower.get(name).cloned().unwrap_or_default();
+
+    // Partitions sit inside their disk, even those of an array
+    if block.join("partition").exists() {
+        let disk: Option<String> = fs::canonicalize(&block)
+            .ok()
+            .and_then(|block| Some(block.parent()?.file_name()?.to_string_lossy().to_string()));
+        if let Some(disk) = disk {
+            walk(root, topology, &disk, layers, disks, depth + 1);
+        }
+        return;
+    }
+    if name.starts_with("dm-") {
+        let uuid: String = read_trimmed(block.join("dm/uuid")).unwrap_or_default();
+        push_layer(
+            layers,
+            Layer {
+                kind: get_device_mapper_kind(&uuid),
+                name: read_trimmed(block.join("dm/name")).unwrap_or(name.to_string()),
+                level: None,
+            },
+        );
+        for lower_name in lower {
+            walk(root, topology, &lower_name, layers, disks, depth + 1);
+        }
+        return;
+    }
+    if name.starts_with("md") {
+        let (level, members) = topology.arrays.get(name).cloned().unwrap_or_default();
+        push_layer(
+            layers,
+            Layer {
+                kind: LayerKind::Raid,
+                name: name.to_string(),
+                level: level.or_else(|| read_trimmed(block.join("md/level"))),
+            },
+        );
+        let members: Vec<String> = match lower.is_empty() {
+            true => members,
+            false => lower,
+        };
+        for member in members {
+            walk(root, topology, &member, layers, disks, depth + 1);
+        }
+        return;
+    }
+    if !disks.iter().any(|disk| disk.name == name) {
+        disks.push(read_physical_disk(root, name));
+    }
+}
+
+// Several members of an array or pool can lead to the same layers further down
+#[cfg(target_os = "linux")]
+fn push_layer(layers: &mut Vec<Layer>, layer: Layer) {
+    if !layers
+        .iter()
+        .any(|existing| existing.kind == layer.kind && existing.name == layer.name)
+    {
+        layers.push(layer);
+    }
+}
+
+// The UUID starts with the subsystem that set the device up, like
+// `CRYPT-LUKS2-<uuid>-<name>` or `LVM-<vg uuid><lv uuid>`
+#[cfg(target_os = "linux")]
+fn get_device_mapper_kind(uuid: &str) -> LayerKind {
+    match uuid {
+        uuid if uuid.starts_with("CRYPT-LUKS") => LayerKind::Luks,
+        uuid if uuid.starts_with("CRYPT-") => LayerKind::Crypt,
+        uuid if uuid.starts_with("LVM-") => LayerKind::Lvm,
+        uuid if uuid.starts_with("mpath-") => LayerKind::Multipath,
+        _ => LayerKind::DeviceMapper,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_physical_disk(root: &Path, name: &str) -> PhysicalDisk {
+    let block: PathBuf = root.join("sys/class/block").join(name);
+    // Loop devices, zram and the like aren't backed by any hardware
+    let kind: DiskKind = match (
+        name.starts_with("nvme"),
+        block.join("device").exists(),
+        read_trimmed(block.join("queue/rotational")).as_deref(),
+    ) {
+        (true, _, _) => DiskKind::Nvme,
+        (false, false, _) => DiskKind::Virtual,
+        (false, true, Some("1")) => DiskKind::Hdd,
+        (false, true, Some("0")) => DiskKind::Ssd,
+        _ => DiskKind::Unknown,
+    };
+    PhysicalDisk {
+        name: name.to_string(),
+        model: read_trimmed(block.join("device/model")),
+        kind,
+        // Always counted in 512 byte sectors, whatever the disk's own sector size
+        size: read_trimmed(block.join("size"))
+            .and_then(|size| size.parse::<u64>().ok())
+            .map(|sectors| sectors * 512),
+    }
+}
+
+// Every device lists the devices built on top of it in `holders`, for disks and partitions alike
+#[cfg(target_os = "linux")]
+fn read_holders(root: &Path) -> BTreeMap<String, Vec<String>> {
+    let mut lower: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let Ok(entries) = fs::read_dir(root.join("sys/block")) else {
+        return lower;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let disk: PathBuf = entry.path();
+        let mut devices: Vec<PathBuf> = vec![disk.clone()];
+        if let Ok(children) = fs::read_dir(&disk) {
+            devices.extend(
+                children
+                    .filter_map(Result::ok)
+                    .map(|child| child.path())
+                    .filter(|child| child.join("partition").exists()),
+            );
+        }
+        for device in devices {
+            let Some(device_name) = device.file_name() else {
+                continue;
+            };
+            let Ok(holders) = fs::read_dir(device.join("holders")) else {
+                continue;
+            };
+            for holder in holders.filter_map(Result::ok) {
+                lower
+                    .entry(holder.file_name().to_string_lossy().to_string())
+                    .or_default()
+                    .push(device_name.to_string_lossy().to_string());
+            }
+        }
+    }
+    for devices in lower.values_mut() {
+        devices.sort();
+    }
+    lower
+}
+
+// Arrays look like `md0 : active raid1 sdb1[1] sda1[0]`, inactive ones don't have a level
+#[cfg(target_os = "linux")]
+fn parse_mdstat(mdstat: &str) -> BTreeMap<String, (Option<String>, Vec<String>)> {
+    let mut arrays: BTreeMap<String, (Option<String>, Vec<String>)> = BTreeMap::new();
+    for line in mdstat.lines() {
+        let Some((name, fields)) = line.split_once(" : ") else {
+            continue;
+        };
+        if !name.starts_with("md") {
+            continue;
+        }
+        let mut level: Option<String> = None;
+        let mut members: Vec<String> = vec![];
+        for field in fields.split_whitespace() {
+            match field {
+                "active" | "inactive" => {}
+                field if field.starts_with('(') => {}
+                field => match field.split_once('[') {
+                    Some((member, _)) => members.push(member.to_string()),
+                    None => level = Some(field.to_string()),
+                },
+            }
+        }
+        members.sort();
+        arrays.insert(name.trim().to_string(), (level, members));
+    }
+    arrays
+}
+
+// Every btrfs filesystem lists its devices in /sys/fs/btrfs, a single device needs no layer
+#[cfg(target_os = "linux")]
+fn get_btrfs_members(root: &Path, name: &str, layers: &mut Vec<Layer>) -> Vec<String> {
+    let filesystems: Vec<PathBuf> = fs::read_dir(root.join("sys/fs/btrfs"))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    for filesystem in filesystems {
+        let Ok(entries) = fs::read_dir(filesystem.join("devices")) else {
+            continue;
+        };
+        let mut members: Vec<String> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        if !members.iter().any(|member| member == name) {
+            continue;
+        }
+        if members.len() > 1 {
+            let uuid: String = filesystem
+                .file_name()
+                .map(|uuid| uuid.to_string_lossy().to_string())
+                .unwrap_or_default();
+            layers.push(Layer {
+                kind: LayerKind::Btrfs,
+                name: read_trimmed(filesystem.join("label")).unwrap_or(uuid),
+                level: None,
+            });
+        }
+        members.sort();
+        return members;
+    }
+    vec![name.to_string()]
+}
+
+// ZFS doesn't register as a holder, but udev tags every member with its pool's name, in files
+// named after the device number, like `b259:2`
+#[cfg(target_os = "linux")]
+fn get_zfs_members(root: &Path, pool: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(root.join("run/udev/data")) else {
+        return vec![];
+    };
+    let mut members: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name: String = entry.file_name().to_string_lossy().to_string();
+            let device_number: &str = file_name.strip_prefix('b')?;
+            let properties: String = fs::read_to_string(entry.path()).ok()?;
+            let is_member: bool = properties
+                .lines()
+                .any(|line| line == "E:ID_FS_TYPE=zfs_member")
+                && properties
+                    .lines()
+                    .any(|line| line.strip_prefix("E:ID_FS_LABEL=") == Some(pool));
+            if !is_member {
+                return None;
+            }
+            let block: PathBuf =
+                fs::canonicalize(root.join("sys/dev/block").join(device_number)).ok()?;
+            Some(block.file_name()?.to_string_lossy().to_string())
+        })
+        .collect();
+    members.sort();
+    members
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    const NVME: &str = "sys/devices/pci0000:00/0000:00:1d.0/0000:3d:00.0/nvme/nvme0";
+    const SATA: &str = "sys/devices/pci0000:00/0000:00:17.0/ata1/host0/target0:0:0";
+
+    // A disk under its controller, with its partitions inside it, linked from /sys/block and
+    // /sys/class/block like the kernel does. No controller makes it a virtual disk
+    fn disk(fixture: &Fixture, controller: Option<&str>, name: &str, partitions: &[&str]) {
+        let path: String = match controller {
+            Some(controller) => format!("{controller}/{name}"),
+            None => format!("sys/devices/virtual/block/{name}"),
+        };
+        fixture.write(&format!("{path}/size"), "2000409264\n");
+        if let Some(controller) = controller {
+            fixture.write(&format!("{controller}/model"), format!("Model of {name}\n"));
+            fixture.symlink(controller, &format!("{path}/device"));
+        }
+        fixture.write(&format!("{path}/queue/rotational"), "0\n");
+        fixture.symlink(&path, &format!("sys/block/{name}"));
+        fixture.symlink(&path, &format!("sys/class/block/{name}"));
+        for partition in partitions {
+            fixture.write(&format!("{path}/{partition}/partition"), "1\n");
+            fixture.symlink(
+                &format!("{path}/{partition}"),
+                &format!("sys/class/block/{partition}"),
+            );
+        }
+    }
+
+    // Device mapper devices are virtual, and each device below lists them as a holder
+    fn device_mapper(fixture: &Fixture, name: &str, dm_name: &str, uuid: &str, lower: &[&str]) {
+        let path: String = format!("sys/devices/virtual/block/{name}");
+        fixture.write(&format!("{path}/dm/name"), format!("{dm_name}\n"));
+        fixture.write(&format!("{path}/dm/uuid"), format!("{uuid}\n"));
+        fixture.symlink(&path, &format!("sys/block/{name}"));
+        fixture.symlink(&path, &format!("sys/class/block/{name}"));
+        hold(fixture, name, lower);
+    }
+
+    fn hold(fixture: &Fixture, holder: &str, lower: &[&str]) {
+        for device in lower {
+            fixture.create_dir(&format!("sys/class/block/{device}/holders/{holder}"));
+        }
+    }
+
+    fn mount(device: &str, filesystem: &str, mount_point: &str) -> Mount {
+        Mount {
+            id: "0:0".to_string(),
+            device: device.to_string(),
+            filesystem: filesystem.to_string(),
+            mount_point: mount_point.to_string(),
+            root: None,
+            read_only: false,
+            space: None,
+            removable: None,
+        }
+    }
+
+    // Kinds as they end up in the snapshot, like `Luks:cryptroot`
+    fn layers(storage: &Storage) -> Vec<String> {
+        storage
+            .layers
+            .iter()
+            .map(|layer| {
+                let kind: String = serde_yaml::to_value(&layer.kind)
+                    .ok()
+                    .and_then(|kind| kind.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                match &layer.level {
+                    Some(level) => format!("{kind}:{}:{level}", layer.name),
+                    None => format!("{kind}:{}", layer.name),
+                }
+            })
+            .collect()
+    }
+
+    fn disks(storage: &Storage) -> Vec<&str> {
+        storage
+            .disks
+            .iter()
+            .map(|disk| disk.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn reads_lvm_on_luks() {
+        let fixture: Fixture = Fixture::new();
+        disk(&fixture, Some(NVME), "nvme0n1", &["nvme0n1p1", "nvme0n1p2"]);
+        device_mapper(
+            &fixture,
+            "dm-0",
+            "cryptroot",
+            "CRYPT-LUKS2-3f4c1a7e9b2d4e6f8a0b1c2d3e4f5a6b-cryptroot",
+            &["nvme0n1p2"],
+        );
+        device_mapper(&fixture, "dm-1", "vg-root", "LVM-Xq3h8CkWv0r5", &["dm-0"]);
+        device_mapper(&fixture, "dm-2", "vg-home", "LVM-Xq3h8CkWv0r6", &["dm-0"]);
+        // LVM volumes can be mounted through their /dev/<vg>/<lv> link
+        fixture.create_dir("dev");
+        fixture.symlink("dev/dm-2", "dev/vg/home");
+        fixture.write("dev/dm-2", "");
+
+        let mounts: Vec<Mount> = vec![
+            mount("/dev/mapper/vg-root", "ext4", "/"),
+            mount("/dev/vg/home", "ext4", "/home"),
+            mount("/dev/nvme0n1p1", "vfat", "/boot"),
+        ];
+        let storage: Vec<Storage> = read_storage(fixture.path(), &mounts);
+        assert_eq!(storage.len(), 3);
+        assert_eq!(layers(&storage[0]), ["Lvm:vg-root", "Luks:cryptroot"]);
+        assert_eq!(disks(&storage[0]), ["nvme0n1"]);
+        assert_eq!(layers(&storage[1]), ["Lvm:vg-home", "Luks:cryptroot"]);
+        assert!(layers(&storage[2]).is_empty());
+        assert_eq!(disks(&storage[2]), ["nvme0n1"]);
+
+        let nvme: &PhysicalDisk = &storage[0].disks[0];
+        assert!(matches!(nvme.kind, DiskKind::Nvme));
+        assert_eq!(nvme.model.as_deref(), Some("Model of nvme0n1"));
+        assert_eq!(nvme.size, Some(2000409264 * 512));
+    }
+
+    #[test]
+    fn reads_mdraid() {
+        let fixture: Fixture = Fixture::new();
+        disk(&fixture, Some(SATA), "sda", &["sda1"]);
+        disk(&fixture, Some(SATA), "sdb", &["sdb1"]);
+        fixture.write(&format!("{SATA}/sda/queue/rotational"), "1\n");
+        fixture.write("sys/devices/virtual/block/md0/md/level", "raid1\n");
+        fixture.symlink("sys/devices/virtual/block/md0", "sys/block/md0");
+        fixture.symlink("sys/devices/virtual/block/md0", "sys/class/block/md0");
+        hold(&fixture, "md0", &["sda1", "sdb1"]);
+        fixture.write(
+            "proc/mdstat",
+            "Personalities : [raid1]\n\
+             md0 : active raid1 sdb1[1] sda1[0]\n      \
+             976630464 blocks super 1.2 [2/2] [UU]\n\n\
+             unused devices: <none>\n",
+        );
+
+        let storage: Vec<Storage> =
+            read_storage(fixture.path(), &[mount("/dev/md0", "ext4", "/srv")]);
+        assert_eq!(layers(&storage[0]), ["Raid:md0:raid1"]);
+        assert_eq!(disks(&storage[0]), ["sda", "sdb"]);
+        assert!(matches!(storage[0].disks[0].kind, DiskKind::Hdd));
+        assert!(matches!(storage[0].disks[1].kind, DiskKind::Ssd));
+    }
+
+    #[test]
+    fn parses_mdstat() {
+        let arrays: BTreeMap<String, (Option<String>, Vec<String>)> = parse_mdstat(
+            "Personalities : [raid1] [raid6] [raid5] [raid4]\n\
+             md1 : active raid5 sdd[2] sdc[1] sde[3](S) sdb[0]\n\
+             md127 : inactive sdf[0](S)\n",
+        );
+        assert_eq!(
+            arrays.get("md1"),
+            Some(&(
+                Some("raid5".to_string()),
+                vec![
+                    "sdb".to_string(),
+                    "sdc".to_string(),
+                    "sdd".to_string(),
+                    "sde".to_string()
+                ]
+            ))
+        );
+        assert_eq!(arrays.get("md127"), Some(&(None, vec!["sdf".to_string()])));
+        assert_eq!(arrays.len(), 2);
+    }
+
+    #[test]
+    fn reads_btrfs_across_devices() {
+        let fixture: Fixture = Fixture::new();
+        disk(&fixture, Some(SATA), "sdc", &[]);
+        disk(&fixture, Some(SATA), "sdd", &[]);
+        disk(&fixture, Some(NVME), "nvme0n1", &["nvme0n1p2"]);
+        let data: &str = "sys/fs/btrfs/5d1e6c2a-8f0b-4c3d-9e7a-1b2c3d4e5f60";
+        fixture.write(&format!("{data}/label"), "data\n");
+        fixture.create_dir(&format!("{data}/devices/sdd"));
+        fixture.create_dir(&format!("{data}/devices/sdc"));
+        // A single device filesystem doesn't get a layer, and one without a label goes by UUID
+        fixture.create_dir("sys/fs/btrfs/0a1b2c3d-0000-4000-8000-000000000001/devices/nvme0n1p2");
+        let scratch: &str = "sys/fs/btrfs/0a1b2c3d-0000-4000-8000-000000000002";
+        fixture.write(&format!("{scratch}/label"), "\n");
+        disk(&fixture, None, "loop0", &[]);
+        disk(&fixture, None, "loop1", &[]);
+        fixture.create_dir(&format!("{scratch}/devices/loop0"));
+        fixture.create_dir(&format!("{scratch}/devices/loop1"));
+
+        let mounts: Vec<Mount> = vec![
+            mount("/dev/sdc", "btrfs", "/data"),
+            mount("/dev/nvme0n1p2", "btrfs", "/"),
+            mount("/dev/loop1", "btrfs", "/scratch"),
+        ];
+        let storage: Vec<Storage> = read_storage(fixture.path(), &mounts);
+        assert_eq!(layers(&storage[0]), ["Btrfs:data"]);
+        assert_eq!(disks(&storage[0]), ["sdc", "sdd"]);
+        assert!(layers(&storage[1]).is_empty());
+        assert_eq!(disks(&storage[1]), ["nvme0n1"]);
+        assert_eq!(
+            layers(&storage[2]),
+            ["Btrfs:0a1b2c3d-0000-4000-8000-000000000002"]
+        );
+        assert_eq!(disks(&storage[2]), ["loop0", "loop1"]);
+        assert!(matches!(storage[2].disks[0].kind, DiskKind::Virtual));
+    }
+
+    #[test]
+    fn reads_zfs_pools() {
+        let fixture: Fixture = Fixture::new();
+        disk(&fixture, Some(NVME), "nvme0n1", &["nvme0n1p3"]);
+        disk(&fixture, Some(SATA), "sda", &["sda1"]);
+        disk(&fixture, Some(SATA), "sdb", &["sdb1"]);
+        let nvme_partition: String = format!("{NVME}/nvme0n1/nvme0n1p3");
+        fixture.symlink(&nvme_partition, "sys/dev/block/259:3");
+        fixture.symlink(&format!("{SATA}/sda/sda1"), "sys/dev/block/8:1");
+        fixture.symlink(&format!("{SATA}/sdb/sdb1"), "sys/dev/block/8:17");
+        let member = |pool: &str| -> String {
+            format!("S:disk/by-uuid/1234\nE:ID_FS_LABEL={pool}\nE:ID_FS_TYPE=zfs_member\nE:ID_FS_USAGE=filesystem\n")
+        };
+        fixture.write("run/udev/data/b259:3", member("rpool"));
+        fixture.write("run/udev/data/b8:1", member("tank"));
+        fixture.write("run/udev/data/b8:17", member("tank"));
+        // Character devices, and filesystems that only share the pool's label, aren't members
+        fixture.write("run/udev/data/c189:1", "E:ID_FS_LABEL=rpool\n");
+        fixture.write(
+            "run/udev/data/b8:0",
+            "E:ID_FS_TYPE=ext4\nE:ID_FS_LABEL=rpool\n",
+        );
+
+        let mounts: Vec<Mount> = vec![
+            mount("rpool/ROOT/ubuntu", "zfs", "/"),
+            mount("tank", "zfs", "/tank"),
+            mount("tmpfs", "tmpfs", "/tmp"),
+        ];
+        let storage: Vec<Storage> = read_storage(fixture.path(), &mounts);
+        assert_eq!(storage.len(), 2);
+        assert_eq!(layers(&storage[0]), ["Zfs:rpool"]);
+        assert_eq!(disks(&storage[0]), ["nvme0n1"]);
+        assert_eq!(layers(&storage[1]), ["Zfs:tank"]);
+        assert_eq!(disks(&storage[1]), ["sda", "sdb"]);
+    }
+
+    #[test]
+    fn tells_device_mapper_targets_apart() {
+        let kind = |uuid: &str| -> String {
+            serde_yaml::to_value(get_device_mapper_kind(uuid))
+                .ok()
+                .and_then(|kind| kind.as_str().map(str::to_string))
+                .unwrap_or_default()
+        };
+        assert_eq!(kind("CRYPT-LUKS1-0123456789abcdef-home"), "Luks");
+        assert_eq!(kind("CRYPT-PLAIN-swap"), "Crypt");
+        assert_eq!(kind("LVM-abcdef"), "Lvm");
+        assert_eq!(kind("mpath-3600508b400105e210000900000490000"), "Multipath");
+        assert_eq!(kind(""), "DeviceMapper");
+    }
+}
@@ -0,0 +1,12 @@
+use std::fs;
+use std::path::Path;
+
+// sysfs and procfs files hold a single value followed by a newline, files that exist but are
+// empty mean the value isn't known either
+pub(crate) fn read_trimmed(path: impl AsRef<Path>) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+pub(crate) fn read_number(path: impl AsRef<Path>) -> Option<u64> {
+    read_trimmed(path)?.parse().ok()
+}
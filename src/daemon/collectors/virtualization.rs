@@ -0,0 +1,237 @@
+use crate::daemon::collectors::main::{Collector, Refresh};
+#[cfg(target_os = "linux")]
+use crate::daemon::collectors::sysfs::read_trimmed;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+
+// Values of the `container` variable that container managers hand to their init
+#[cfg(target_os = "linux")]
+const CONTAINER_NAMES: &[(&str, &str)] = &[
+    ("docker", "Docker"),
+    ("podman", "Podman"),
+    ("lxc", "LXC"),
+    ("lxc-libvirt", "LXC"),
+    ("systemd-nspawn", "systemd-nspawn"),
+];
+
+// Path segments that container managers give their cgroups
+#[cfg(target_os = "linux")]
+const CGROUP_MARKERS: &[(&str, &str)] = &[
+    ("/docker/", "Docker"),
+    ("/docker-", "Docker"),
+    ("/libpod-", "Podman"),
+    ("/lxc/", "LXC"),
+    ("/lxc.payload", "LXC"),
+    ("/machine.slice/systemd-nspawn", "systemd-nspawn"),
+];
+
+// Vendor strings of the DMI tables that hypervisors fill in, matched against the system and
+// board vendor, product name and BIOS vendor
+#[cfg(target_os = "linux")]
+const DMI_VENDORS: &[(&str, &str)] = &[
+    ("KVM", "KVM"),
+    ("QEMU", "QEMU"),
+    ("VMware", "VMware"),
+    ("VMW", "VMware"),
+    ("innotek GmbH", "VirtualBox"),
+    ("VirtualBox", "VirtualBox"),
+    ("Xen", "Xen"),
+    ("Parallels", "Parallels"),
+    ("bhyve", "bhyve"),
+    ("Amazon EC2", "Amazon EC2"),
+    ("Google Compute Engine", "Google Compute Engine"),
+    ("OpenStack", "OpenStack"),
+];
+
+// Signatures hypervisors put in CPUID leaf 0x40000000
+#[cfg(target_arch = "x86_64")]
+const CPUID_VENDORS: &[(&str, &str)] = &[
+    ("KVMKVMKVM", "KVM"),
+    ("TCGTCGTCGTCG", "QEMU"),
+    ("Microsoft Hv", "Hyper-V"),
+    ("VMwareVMware", "VMware"),
+    ("VBoxVBoxVBox", "VirtualBox"),
+    ("XenVMMXenVMM", "Xen"),
+    ("bhyve bhyve ", "bhyve"),
+    (" lrpepyh  vr", "Parallels"),
+    ("ACRNACRNACRN", "ACRN"),
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Virtualization {
+    pub(crate) kind: VirtualizationKind,
+    pub(crate) technology: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum VirtualizationKind {
+    BareMetal,
+    VirtualMachine,
+    Container,
+    Unknown,
+}
+
+pub(crate) struct VirtualizationCollector;
+
+impl Collector for VirtualizationCollector {
+    type Output = Virtualization;
+
+    fn name(&self) -> &str {
+        "virtualization"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Once
+    }
+
+    async fn collect(&self) -> Virtualization {
+        get_virtualization()
+    }
+}
+
+// A container is the closer boundary, so it wins over the virtual machine it might run in
+#[cfg(target_os = "linux")]
+pub(crate) fn get_virtualization() -> Virtualization {
+    let root: &Path = Path::new("/");
+    if let Some(technology) = detect_container(root) {
+        return Virtualization {
+            kind: VirtualizationKind::Container,
+            technology: Some(technology),
+        };
+    }
+    // QEMU's tables don't say whether KVM is accelerating it, CPUID does
+    let technology: Option<String> = match detect_dmi_hypervisor(root) {
+        Some(technology) if technology == "QEMU" => detect_cpuid_hypervisor().or(Some(technology)),
+        Some(technology) => Some(technology),
+        None => detect_cpuid_hypervisor(),
+    };
+    if let Some(technology) = technology {
+        return Virtualization {
+            kind: VirtualizationKind::VirtualMachine,
+            technology: Some(technology),
+        };
+    }
+    // Other architectures only flag that there is a hypervisor, not which one
+    let cpuinfo: String = fs::read_to_string(root.join("proc/cpuinfo")).unwrap_or_default();
+    let flagged: bool = cpuinfo
+        .lines()
+        .filter(|line| line.starts_with("flags"))
+        .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"));
+    Virtualization {
+        kind: match flagged {
+            true => VirtualizationKind::VirtualMachine,
+            false => VirtualizationKind::BareMetal,
+        },
+        technology: None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn get_virtualization() -> Virtualization {
+    match detect_cpuid_hypervisor() {
+        Some(technology) => Virtualization {
+            kind: VirtualizationKind::VirtualMachine,
+            technology: Some(technology),
+        },
+        None => Virtualization {
+            kind: match cfg!(target_arch = "x86_64") {
+                true => VirtualizationKind::BareMetal,
+                false => VirtualizationKind::Unknown,
+            },
+            technology: None,
+        },
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_container(root: &Path) -> Option<String> {
+    let container_name = |name: &str| -> String {
+        CONTAINER_NAMES
+            .iter()
+            .find(|(value, _)| *value == name)
+            .map(|(_, technology)| technology.to_string())
+            .unwrap_or(name.to_string())
+    };
+    // Only root may read init's environment, systemd copies the variable for everyone else
+    let environ: Vec<u8> = fs::read(root.join("proc/1/environ")).unwrap_or_default();
+    let from_environ: Option<String> = environ
+        .split(|byte| *byte == 0)
+        .find_map(|variable| variable.strip_prefix(b"container="))
+        .map(|name| String::from_utf8_lossy(name).to_string());
+    if let Some(name) = from_environ.or_else(|| read_trimmed(root.join("run/systemd/container"))) {
+        return Some(container_name(&name));
+    }
+    if root.join(".dockerenv").exists() {
+        return Some("Docker".to_string());
+    }
+    if root.join("run/.containerenv").exists() {
+        return Some("Podman".to_string());
+    }
+    let cgroup: String = fs::read_to_string(root.join("proc/1/cgroup")).unwrap_or_default();
+    if let Some((_, technology)) = CGROUP_MARKERS
+        .iter()
+        .find(|(marker, _)| cgroup.contains(marker))
+    {
+        return Some(technology.to_string());
+    }
+    // WSL's kernel names itself, like `5.15.90.1-microsoft-standard-WSL2`
+    let release: String = read_trimmed(root.join("proc/sys/kernel/osrelease")).unwrap_or_default();
+    if release.to_lowercase().contains("microsoft")
+        || root.join("proc/sys/fs/binfmt_misc/WSLInterop").exists()
+    {
+        return Some("WSL".to_string());
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn detect_dmi_hypervisor(root: &Path) -> Option<String> {
+    let dmi: &Path = &root.join("sys/class/dmi/id");
+    let fields: Vec<String> = ["sys_vendor", "product_name", "board_vendor", "bios_vendor"]
+        .iter()
+        .filter_map(|field| read_trimmed(dmi.join(field)))
+        .collect();
+    // Hyper-V shares its vendor with Microsoft's own hardware, only the product tells them apart
+    if fields.iter().any(|field| field == "Microsoft Corporation")
+        && fields.iter().any(|field| field == "Virtual Machine")
+    {
+        return Some("Hyper-V".to_string());
+    }
+    DMI_VENDORS
+        .iter()
+        .find(|(vendor, _)| fields.iter().any(|field| field.starts_with(vendor)))
+        .map(|(_, technology)| technology.to_string())
+}
+
+// Leaf 1 flags a hypervisor, which then signs leaf 0x40000000 with twelve bytes of its name.
+// Hosts running Hyper-V themselves sit in its root partition, which may create partitions
+#[cfg(target_arch = "x86_64")]
+fn detect_cpuid_hypervisor() -> Option<String> {
+    use std::arch::x86_64::{__cpuid, CpuidResult};
+
+    if __cpuid(1).ecx & (1 << 31) == 0 {
+        return None;
+    }
+    let leaf: CpuidResult = __cpuid(0x4000_0000);
+    let signature: Vec<u8> = [leaf.ebx, leaf.ecx, leaf.edx]
+        .iter()
+        .flat_map(|register| register.to_le_bytes())
+        .collect();
+    let signature: String = String::from_utf8_lossy(&signature).to_string();
+    let technology: String = CPUID_VENDORS
+        .iter()
+        .find(|(vendor, _)| signature.starts_with(vendor))
+        .map(|(_, technology)| technology.to_string())?;
+    if technology == "Hyper-V" && __cpuid(0x4000_0003).ebx & 1 != 0 {
+        return None;
+    }
+    Some(technology)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn detect_cpuid_hypervisor() -> Option<String> {
+    None
+}
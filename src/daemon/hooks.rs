@@ -0,0 +1,313 @@
+use crate::config::daemon::{Hook, Trigger, DAEMON_CONFIG_OBJECT};
+use crate::daemon::fetch_info::SystemInfo;
+use crate::daemon::main::SYSTEM_INFO_MUTEX;
+use crate::daemon::snapshot::{lookup, render_value};
+use lazy_static::lazy_static;
+use serde_yaml::Value;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+
+const HOOK_INTERVAL: Duration = Duration::from_secs(1);
+
+lazy_static! {
+    static ref HOOK_STATES: Mutex<Vec<HookState>> = Mutex::new(
+        DAEMON_CONFIG_OBJECT
+            .hooks
+            .iter()
+            .map(|_| HookState::default())
+            .collect()
+    );
+}
+
+#[derive(Default)]
+struct HookState {
+    // The last value that was seen without anything pending, this is what "old" refers to
+    last: Option<Value>,
+    pending: Option<PendingEvent>,
+}
+
+struct PendingEvent {
+    old: Value,
+    new: Value,
+    since: Instant,
+}
+
+pub(crate) async fn loop_evaluate_hooks() {
+    if DAEMON_CONFIG_OBJECT.hooks.is_empty() {
+        return;
+    }
+    loop {
+        let system_info: SystemInfo = SYSTEM_INFO_MUTEX
+            .lock()
+            .expect("Failed to lock system info mutex")
+            .clone();
+        let snapshot: Value =
+            serde_yaml::to_value(system_info).expect("Failed to serialize system info");
+        evaluate_hooks(&snapshot);
+        tokio::time::sleep(HOOK_INTERVAL).await;
+    }
+}
+
+fn evaluate_hooks(snapshot: &Value) {
+    let mut states: MutexGuard<Vec<HookState>> =
+        HOOK_STATES.lock().expect("Failed to lock hook state mutex");
+    for (hook, state) in DAEMON_CONFIG_OBJECT.hooks.iter().zip(states.iter_mut()) {
+        // A value that isn't there (yet) can't trigger anything, it also isn't a change
+        let Some(current) = watched_value(hook, snapshot) else {
+            continue;
+        };
+        if let Some((old, new)) = evaluate_hook(hook, state, current, Instant::now()) {
+            tokio::spawn(run_hook(hook.clone(), old, new));
+        }
+    }
+}
+
+// An event has to hold for the debounce period before the hook runs, for changes that means the
+// value has to settle, for thresholds it has to stay on the other side the whole time. Gives the
+// old and new value once the hook should run
+fn evaluate_hook(
+    hook: &Hook,
+    state: &mut HookState,
+    current: Value,
+    now: Instant,
+) -> Option<(Value, Value)> {
+    let Some(last) = state.last.clone() else {
+        state.last = Some(current);
+        return None;
+    };
+    match state.pending.as_mut() {
+        None if crossed(hook, &last, &current) => {
+            state.pending = Some(PendingEvent {
+                old: last,
+                new: current,
+                since: now,
+            });
+        }
+        None => state.last = Some(current),
+        Some(pending) if !holds(hook, &pending.old, &current) => {
+            state.pending = None;
+            state.last = Some(current);
+        }
+        Some(pending) => {
+            if hook.trigger == Trigger::Change && pending.new != current {
+                pending.since = now;
+            }
+            pending.new = current;
+        }
+    }
+
+    let debounce: Duration = Duration::from_secs(hook.debounce_seconds);
+    let pending: PendingEvent = state
+        .pending
+        .take_if(|pending| now.duration_since(pending.since) >= debounce)?;
+    state.last = Some(pending.new.clone());
+    Some((pending.old, pending.new))
+}
+
+fn watched_value(hook: &Hook, snapshot: &Value) -> Option<Value> {
+    let value: &Value = lookup(snapshot, &hook.watch)?;
+    match &hook.relative_to {
+        None => Some(value.clone()),
+        Some(relative_to) => {
+            let divisor: f64 = lookup(snapshot, relative_to)?.as_f64()?;
+            if divisor == 0.0 {
+                return None;
+            }
+            Some(Value::from(value.as_f64()? / divisor))
+        }
+    }
+}
+
+fn crossed(hook: &Hook, old: &Value, new: &Value) -> bool {
+    match hook.trigger {
+        Trigger::Change => old != new,
+        Trigger::Above | Trigger::Below => !holds(hook, old, old) && holds(hook, old, new),
+    }
+}
+
+fn holds(hook: &Hook, old: &Value, current: &Value) -> bool {
+    match hook.trigger {
+        Trigger::Change => old != current,
+        Trigger::Above => current.as_f64().is_some_and(|value| value > hook.threshold),
+        Trigger::Below => current.as_f64().is_some_and(|value| value < hook.threshold),
+    }
+}
+
+async fn run_hook(hook: Hook, old: Value, new: Value) {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let mut command: Command = Command::new("sh");
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    command.arg("-c");
+    #[cfg(target_os = "windows")]
+    let mut command: Command = Command::new("cmd");
+    #[cfg(target_os = "windows")]
+    command.arg("/C");
+
+    let child: Result<Child, std::io::Error> = command
+        .arg(&hook.command)
+        .env("HAYABUSA_HOOK", &hook.name)
+        .env("HAYABUSA_WATCH", &hook.watch)
+        .env("HAYABUSA_OLD_VALUE", render_value(&old))
+        .env("HAYABUSA_NEW_VALUE", render_value(&new))
+        .spawn();
+    let mut child: Child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to run hook {}: {}", hook.name, e);
+            return;
+        }
+    };
+
+    let timeout: Duration = Duration::from_secs(hook.timeout_seconds);
+    match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) if status.success() => println!("Ran hook {}", hook.name),
+        Ok(Ok(status)) => eprintln!("Hook {} exited with {}", hook.name, status),
+        Ok(Err(e)) => eprintln!("Failed to wait on hook {}: {}", hook.name, e),
+        Err(_) => {
+            eprintln!("Hook {} timed out after {:?}", hook.name, timeout);
+            if let Err(e) = child.kill().await {
+                eprintln!("Failed to kill hook {}: {}", hook.name, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(yaml: &str) -> Hook {
+        serde_yaml::from_str(&format!("name: test\ncommand: 'true'\n{yaml}"))
+            .expect("Test hooks are valid")
+    }
+
+    // Feeds the values in at the given seconds, and gives the events that would run the hook
+    fn run(hook: &Hook, values: &[(u64, Value)]) -> Vec<(u64, Value, Value)> {
+        let start: Instant = Instant::now();
+        let mut state: HookState = HookState::default();
+        values
+            .iter()
+            .filter_map(|(second, value)| {
+                let now: Instant = start + Duration::from_secs(*second);
+                let (old, new) = evaluate_hook(hook, &mut state, value.clone(), now)?;
+                Some((*second, old, new))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fires_on_a_change() {
+        let hook: Hook = hook("watch: local_ip\ndebounce_seconds: 0");
+        let events = run(
+            &hook,
+            &[
+                (0, Value::from("10.0.0.2")),
+                (1, Value::from("10.0.0.2")),
+                (2, Value::from("10.0.0.7")),
+                (3, Value::from("10.0.0.7")),
+                (4, Value::from("10.0.0.2")),
+            ],
+        );
+        // The first value is only what later ones are compared to
+        assert_eq!(
+            events,
+            [
+                (2, Value::from("10.0.0.2"), Value::from("10.0.0.7")),
+                (4, Value::from("10.0.0.7"), Value::from("10.0.0.2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn waits_for_a_change_to_settle() {
+        let hook: Hook = hook("watch: local_ip\ndebounce_seconds: 10");
+        let events = run(
+            &hook,
+            &[
+                (0, Value::from("a")),
+                (1, Value::from("b")),
+                (6, Value::from("c")),
+                // Ten seconds after the first change, but only five after the last
+                (11, Value::from("c")),
+                (16, Value::from("c")),
+                (17, Value::from("c")),
+            ],
+        );
+        assert_eq!(events, [(16, Value::from("a"), Value::from("c"))]);
+    }
+
+    #[test]
+    fn ignores_a_change_flapping_back() {
+        let hook: Hook = hook("watch: local_ip\ndebounce_seconds: 10");
+        let events = run(
+            &hook,
+            &[
+                (0, Value::from("a")),
+                (1, Value::from("b")),
+                (5, Value::from("a")),
+                (20, Value::from("a")),
+            ],
+        );
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn fires_when_crossing_a_threshold() {
+        let hook: Hook = hook("watch: disk\ntrigger: Above\nthreshold: 0.9\ndebounce_seconds: 10");
+        let events = run(
+            &hook,
+            &[
+                (0, Value::from(0.85)),
+                // Changes below the threshold don't count
+                (1, Value::from(0.88)),
+                (2, Value::from(0.92)),
+                (7, Value::from(0.95)),
+                (12, Value::from(0.93)),
+                // Staying above doesn't fire again
+                (30, Value::from(0.96)),
+                (31, Value::from(0.5)),
+                (32, Value::from(0.91)),
+                (42, Value::from(0.91)),
+            ],
+        );
+        assert_eq!(
+            events,
+            [
+                (12, Value::from(0.88), Value::from(0.93)),
+                (42, Value::from(0.5), Value::from(0.91)),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_a_threshold_crossed_only_briefly() {
+        let hook: Hook = hook("watch: disk\ntrigger: Below\nthreshold: 10\ndebounce_seconds: 10");
+        let events = run(
+            &hook,
+            &[
+                (0, Value::from(20)),
+                (1, Value::from(5)),
+                (8, Value::from(12)),
+                (20, Value::from(15)),
+            ],
+        );
+        assert!(events.is_empty());
+
+        // Already past the threshold when the daemon starts, which isn't crossing it
+        let events = run(&hook, &[(0, Value::from(5)), (20, Value::from(4))]);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn watches_ratios() {
+        let snapshot: Value =
+            serde_yaml::from_str("disks:\n  - name: /\n    used: 45\n    total: 50\n").unwrap();
+        let hook: Hook = hook("watch: disks[/].used\nrelative_to: disks[/].total\ntrigger: Above");
+        assert_eq!(watched_value(&hook, &snapshot), Some(Value::from(0.9)));
+
+        let empty: Value =
+            serde_yaml::from_str("disks:\n  - name: /\n    used: 0\n    total: 0\n").unwrap();
+        assert_eq!(watched_value(&hook, &empty), None);
+    }
+}
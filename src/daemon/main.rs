@@ -1,7 +1,10 @@
+use crate::daemon::collectors::main::{build_registry, CollectorRegistry};
 use crate::daemon::fetch_info::{loop_update_system_info, serialize_fetch, SystemInfo, SYS};
+use crate::daemon::{hooks, snapshot};
 use crate::{daemon::fetch_info, SOCKET_PATH};
 use interprocess::local_socket::{LocalSocketListener, LocalSocketStream};
 use lazy_static::lazy_static;
+use serde_yaml::Value;
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 use std::fs::Permissions;
 use std::io::Write;
@@ -11,12 +14,13 @@ use std::sync::{Mutex, MutexGuard};
 use sysinfo::{System, SystemExt};
 
 lazy_static! {
-    pub(crate) static ref SYSTEM_INFO_MUTEX: Mutex<Option<SystemInfo>> = Mutex::new(None);
+    pub(crate) static ref SYSTEM_INFO_MUTEX: Mutex<SystemInfo> = Mutex::new(SystemInfo::new());
 }
 
 pub(crate) async fn main() {
     println!("Running as daemon");
-    initialize_system_info().await;
+    let registry: CollectorRegistry = build_registry();
+    initialize_system_info(&registry).await;
 
     let socket_path: String = SOCKET_PATH.clone();
 
@@ -25,7 +29,8 @@ pub(crate) async fn main() {
         std::fs::remove_file(&socket_path).expect("Failed to remove socket");
     }
 
-    tokio::spawn(loop_update_system_info());
+    tokio::spawn(loop_update_system_info(registry));
+    tokio::spawn(hooks::loop_evaluate_hooks());
 
     // The listener is the IPC server that listens for connections from the fetch client
     let listener: LocalSocketListener =
@@ -54,7 +59,7 @@ pub(crate) async fn main() {
     unreachable!("Unexpected termination: Listener loop exited");
 }
 
-async fn initialize_system_info() {
+async fn initialize_system_info(registry: &CollectorRegistry) {
     {
         // The system_info crate requires that the sys object be refreshed at least once before
         // any info is available
@@ -62,13 +67,20 @@ async fn initialize_system_info() {
         sys.refresh_all();
         // Don't forget to always drop the lock on mutexes ASAP
     }
-    let system_info: SystemInfo = fetch_info::fetch_all().await;
+    let system_info: SystemInfo = fetch_info::fetch_all(registry).await;
+    let boot_time: u64 = system_info
+        .get("boot_time")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if let Err(e) = snapshot::rotate_boot_snapshot(boot_time) {
+        eprintln!("Failed to keep the previous boot snapshot: {}", e);
+    }
     {
         // Here is where we initialize the system info struct we've defined ourselves
-        let mut system_info_mutex_guard: MutexGuard<Option<SystemInfo>> = SYSTEM_INFO_MUTEX
+        let mut system_info_mutex_guard: MutexGuard<SystemInfo> = SYSTEM_INFO_MUTEX
             .lock()
             .expect("Failed to lock system info mutex");
-        *system_info_mutex_guard =
-            Option::from(Some(system_info)).expect("Failed to initialize system info");
+        *system_info_mutex_guard = system_info;
     }
+    snapshot::save_current_snapshot();
 }
@@ -1,3 +1,6 @@
+pub(crate) mod collectors;
 pub(crate) mod fetch_info;
+pub(crate) mod hooks;
 pub(crate) mod main;
 pub(crate) mod package_managers;
+pub(crate) mod snapshot;
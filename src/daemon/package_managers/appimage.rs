@@ -0,0 +1,83 @@
+use crate::config::daemon::DAEMON_CONFIG_OBJECT;
+use crate::daemon::package_managers::main::{get_user_homes, PackageManager};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// AppImages are executables that mark themselves after the ELF identification, with `AI` and
+// the version of the AppImage format
+const MAGIC_OFFSET: usize = 8;
+const MAGICS: &[&[u8]] = &[b"AI\x01", b"AI\x02"];
+
+// AppImages aren't installed anywhere, they're counted in the configured directories
+pub(crate) struct AppImage;
+
+impl AppImage {
+    fn get_directories(&self) -> Vec<PathBuf> {
+        expand_directories(
+            &DAEMON_CONFIG_OBJECT.packages.appimage_directories,
+            &get_user_homes()
+                .into_iter()
+                .map(|(_, home)| home)
+                .collect::<Vec<PathBuf>>(),
+        )
+    }
+}
+
+impl PackageManager for AppImage {
+    fn name(&self) -> &str {
+        "appimage"
+    }
+
+    fn is_present(&self) -> bool {
+        cfg!(target_os = "linux")
+            && self
+                .get_directories()
+                .iter()
+                .any(|directory| directory.is_dir())
+    }
+
+    fn count(&self) -> Result<u64, String> {
+        Ok(count_appimages(&self.get_directories()))
+    }
+}
+
+// A leading `~` stands for every home directory
+pub(crate) fn expand_directories(directories: &[String], homes: &[PathBuf]) -> Vec<PathBuf> {
+    directories
+        .iter()
+        .flat_map(|directory| {
+            match directory
+                .strip_prefix('~')
+                .filter(|relative| relative.is_empty() || relative.starts_with('/'))
+            {
+                Some(relative) => homes
+                    .iter()
+                    .map(|home| home.join(relative.trim_start_matches('/')))
+                    .collect(),
+                None => vec![PathBuf::from(directory)],
+            }
+        })
+        .collect()
+}
+
+// The same file can be reached from more than one directory, or through a link
+pub(crate) fn count_appimages(directories: &[PathBuf]) -> u64 {
+    let appimages: BTreeSet<PathBuf> = directories
+        .iter()
+        .filter_map(|directory| fs::read_dir(directory).ok())
+        .flat_map(|entries| entries.filter_map(Result::ok))
+        .filter_map(|entry| fs::canonicalize(entry.path()).ok())
+        .filter(|path| path.is_file() && is_appimage(path))
+        .collect();
+    appimages.len() as u64
+}
+
+fn is_appimage(path: &Path) -> bool {
+    let mut header: [u8; MAGIC_OFFSET + 3] = [0; MAGIC_OFFSET + 3];
+    let read: bool = File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok();
+    read && header.starts_with(b"\x7fELF") && MAGICS.contains(&&header[MAGIC_OFFSET..])
+}
@@ -0,0 +1,498 @@
+use crate::daemon::package_managers::compression::read_decompressed;
+use crate::daemon::package_managers::main::{
+    command_exists, command_output, version_word, PackageManager, Updates,
+};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DPKG_STATUS: &str = "/var/lib/dpkg/status";
+const APT_LISTS: &str = "/var/lib/apt/lists";
+
+pub(crate) struct Apt;
+
+impl PackageManager for Apt {
+    fn name(&self) -> &str {
+        "apt"
+    }
+
+    fn is_present(&self) -> bool {
+        command_exists("apt")
+    }
+
+    // apt takes seconds and warns about its unstable CLI, dpkg's database is a single file
+    fn count(&self) -> Result<u64, String> {
+        read_dpkg_status(Path::new(DPKG_STATUS)).or_else(|_| {
+            let stdout: String =
+                command_output("dpkg-query", &["-f", "${db:Status-Abbrev}\n", "-W"])?;
+            Ok(stdout.lines().filter(|line| line.starts_with("ii")).count() as u64)
+        })
+    }
+
+    fn version(&self) -> Option<String> {
+        version_word("apt", &["--version"], 1)
+    }
+
+    fn updates(&self) -> Option<Updates> {
+        read_apt_updates(Path::new(DPKG_STATUS), Path::new(APT_LISTS)).ok()
+    }
+}
+
+// The fields of a package's stanza that are needed, the rest are skipped
+#[derive(Default)]
+struct Stanza<'a> {
+    package: Option<&'a str>,
+    architecture: Option<&'a str>,
+    version: Option<&'a str>,
+    status: Option<&'a str>,
+}
+
+impl Stanza<'_> {
+    // Removed packages can stay behind with their configuration files, only those whose status
+    // ends in `installed` count
+    fn is_installed(&self) -> bool {
+        self.status
+            .is_some_and(|status| status.split_whitespace().nth(2) == Some("installed"))
+    }
+}
+
+// A stanza per package, separated by blank lines
+pub(crate) fn read_dpkg_status(path: &Path) -> Result<u64, String> {
+    let status: String = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let count: usize = parse_stanzas(&status)
+        .iter()
+        .filter(|stanza| stanza.is_installed())
+        .count();
+    Ok(count as u64)
+}
+
+// An installed package can be upgraded when any list apt would pick from has a newer version of
+// it, for the same architecture, and it's a security update when the newest version comes from a
+// security suite. Those are told apart by their name, and suites like backports are only picked
+// from when asked to. Pinning isn't looked at
+pub(crate) fn read_apt_updates(status: &Path, lists: &Path) -> Result<Updates, String> {
+    let status: String = fs::read_to_string(status).map_err(|e| e.to_string())?;
+    let installed: BTreeMap<(&str, &str), &str> = parse_stanzas(&status)
+        .iter()
+        .filter(|stanza| stanza.is_installed())
+        .filter_map(|stanza| Some(((stanza.package?, stanza.architecture?), stanza.version?)))
+        .collect();
+
+    // The newest version of each package, and whether a security suite has it
+    let mut candidates: BTreeMap<(String, String), (String, bool)> = BTreeMap::new();
+    for entry in fs::read_dir(lists).map_err(|e| e.to_string())? {
+        let path: PathBuf = entry.map_err(|e| e.to_string())?.path();
+        let name: String = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // Compressed lists keep their extension, like `..._main_binary-amd64_Packages.lz4`
+        let list_name: &str = [".gz", ".lz4", ".xz", ".zst"]
+            .iter()
+            .find_map(|extension| name.strip_suffix(extension))
+            .unwrap_or(&name);
+        if !list_name.ends_with("_Packages") || is_not_automatic(lists, &name) {
+            continue;
+        }
+        let Ok(list) = read_decompressed(&path) else {
+            continue;
+        };
+        let list: String = String::from_utf8_lossy(&list).to_string();
+        for stanza in parse_stanzas(&list) {
+            let (Some(package), Some(architecture), Some(version)) =
+                (stanza.package, stanza.architecture, stanza.version)
+            else {
+                continue;
+            };
+            let Some(installed_version) = installed.get(&(package, architecture)) else {
+                continue;
+            };
+            if compare_versions(version, installed_version) != Ordering::Greater {
+                continue;
+            }
+            let is_security: bool = name.contains("security");
+            let key: (String, String) = (package.to_string(), architecture.to_string());
+            match candidates.get_mut(&key) {
+                Some((candidate, security)) => match compare_versions(version, candidate) {
+                    Ordering::Greater => {
+                        *candidate = version.to_string();
+                        *security = is_security;
+                    }
+                    Ordering::Equal => *security |= is_security,
+                    Ordering::Less => {}
+                },
+                None => {
+                    candidates.insert(key, (version.to_string(), is_security));
+                }
+            }
+        }
+    }
+    Ok(Updates {
+        count: candidates.len() as u64,
+        security: Some(
+            candidates
+                .values()
+                .filter(|(_, security)| *security)
+                .count() as u64,
+        ),
+    })
+}
+
+// Lists are named after their repository and suite, like
+// `deb.debian.org_debian_dists_bookworm-backports_main_binary-amd64_Packages`, and the suite's
+// release file says whether apt only installs from it when asked to
+fn is_not_automatic(lists: &Path, name: &str) -> bool {
+    let Some(dists) = name.find("_dists_") else {
+        return false;
+    };
+    let suite_start: usize = dists + "_dists_".len();
+    let Some(suite_length) = name[suite_start..].find('_') else {
+        return false;
+    };
+    let prefix: &str = &name[..suite_start + suite_length];
+    let release: String = ["InRelease", "Release"]
+        .iter()
+        .find_map(|file| fs::read_to_string(lists.join(format!("{prefix}_{file}"))).ok())
+        .unwrap_or_default();
+    let field = |key: &str| -> bool {
+        release
+            .lines()
+            .any(|line| line.strip_prefix(key) == Some(": yes"))
+    };
+    field("NotAutomatic") && !field("ButAutomaticUpgrades")
+}
+
+fn parse_stanzas(text: &str) -> Vec<Stanza<'_>> {
+    let mut stanzas: Vec<Stanza> = vec![];
+    let mut stanza: Stanza = Stanza::default();
+    for line in text.lines() {
+        if line.is_empty() {
+            if stanza.package.is_some() {
+                stanzas.push(stanza);
+            }
+            stanza = Stanza::default();
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value: Option<&str> = Some(value.trim());
+        match key {
+            "Package" => stanza.package = value,
+            "Architecture" => stanza.architecture = value,
+            "Version" => stanza.version = value,
+            "Status" => stanza.status = value,
+            _ => {}
+        }
+    }
+    if stanza.package.is_some() {
+        stanzas.push(stanza);
+    }
+    stanzas
+}
+
+// Versions are `epoch:upstream-revision`, where the epoch and revision are optional
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |version: &str| -> (u64, String, String) {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => (epoch.parse().unwrap_or(0), rest),
+            None => (0, version),
+        };
+        let (upstream, revision) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        (epoch, upstream.to_string(), revision.to_string())
+    };
+    let (a_epoch, a_upstream, a_revision) = split(a);
+    let (b_epoch, b_upstream, b_revision) = split(b);
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| compare_parts(&a_upstream, &b_upstream))
+        .then_with(|| compare_parts(&a_revision, &b_revision))
+}
+
+// dpkg goes over alternating runs of non-digits and digits. Non-digits compare by character,
+// with `~` sorting before everything, even the end of the string, and letters before the rest.
+// Digits compare as numbers
+fn compare_parts(a: &str, b: &str) -> Ordering {
+    let (a, b): (&[u8], &[u8]) = (a.as_bytes(), b.as_bytes());
+    let order = |character: Option<&u8>| -> i32 {
+        match character {
+            None => 0,
+            Some(character) if character.is_ascii_digit() => 0,
+            Some(character) if character.is_ascii_alphabetic() => *character as i32,
+            Some(b'~') => -1,
+            Some(character) => *character as i32 + 256,
+        }
+    };
+    let (mut i, mut j): (usize, usize) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let ordering: Ordering = order(a.get(i)).cmp(&order(b.get(j)));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_difference: Ordering = Ordering::Equal;
+        while i < a.len() && a[i].is_ascii_digit() && j < b.len() && b[j].is_ascii_digit() {
+            if first_difference == Ordering::Equal {
+                first_difference = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if i < a.len() && a[i].is_ascii_digit() {
+            return Ordering::Greater;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            return Ordering::Less;
+        }
+        if first_difference != Ordering::Equal {
+            return first_difference;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn counts_installed_packages() {
+        let fixture: Fixture = Fixture::new();
+        let status: &str = "\
+Package: bash
+Status: install ok installed
+Priority: required
+Architecture: amd64
+Version: 5.2.15-2+b7
+Description: GNU Bourne Again SHell
+ Bash is an sh-compatible command language interpreter.
+ .
+ Status: not a field, descriptions go on in indented lines
+
+Package: nano
+Status: deinstall ok config-files
+Architecture: amd64
+Version: 7.2-1
+
+Package: libc6
+Status: install ok installed
+Architecture: i386
+Version: 2.36-9+deb12u4
+
+Package: vim
+Status: install ok half-installed
+Architecture: amd64
+Version: 2:9.0.1378-2
+
+Package: zlib1g
+Status: hold ok installed
+Architecture: amd64
+Version: 1:1.2.13.dfsg-1";
+        let path: PathBuf = fixture.write("status", status);
+        assert_eq!(read_dpkg_status(&path), Ok(3));
+
+        assert_eq!(read_dpkg_status(&fixture.write("empty", "")), Ok(0));
+        assert!(read_dpkg_status(&fixture.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn compares_versions_like_dpkg() {
+        let cases: &[(&str, &str, Ordering)] = &[
+            ("1.0", "1.0", Ordering::Equal),
+            ("10", "9", Ordering::Greater),
+            ("1.001", "1.1", Ordering::Equal),
+            // `~` sorts before everything, even the end, so prereleases come first
+            ("1.0~rc1", "1.0", Ordering::Less),
+            ("1.0~~", "1.0~~a", Ordering::Less),
+            ("1.0~~a", "1.0~", Ordering::Less),
+            ("1.0~", "1.0", Ordering::Less),
+            ("1.0", "1.0a", Ordering::Less),
+            ("1.0", "1.0+b1", Ordering::Less),
+            // Letters sort before the other characters
+            ("1.0a", "1.0+", Ordering::Less),
+            // Epochs win over everything after them, and a missing one is 0
+            ("1:0.1", "2.0", Ordering::Greater),
+            ("0:1.0", "1.0", Ordering::Equal),
+            ("2:9.0.1378-2", "1:9.1.0-1", Ordering::Greater),
+            // Revisions only count when the upstream versions are the same
+            ("1.0-1", "1.0-2", Ordering::Less),
+            ("1.0-10", "1.0-9", Ordering::Greater),
+            ("1.0-1", "1.0", Ordering::Greater),
+            ("1.1", "1.0-9", Ordering::Greater),
+            ("1.0-1ubuntu1", "1.0-1", Ordering::Greater),
+            ("2.36-9+deb12u4", "2.36-9", Ordering::Greater),
+            ("3.0.11-1~deb12u1", "3.0.11-1", Ordering::Less),
+            // Upstream versions can have hyphens, the revision is after the last one
+            ("1.2-3-4", "1.2-3-5", Ordering::Less),
+            ("1.2-3-4", "1.2-4-1", Ordering::Less),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare_versions(a, b), *expected, "{a} against {b}");
+            assert_eq!(
+                compare_versions(b, a),
+                expected.reverse(),
+                "{b} against {a}"
+            );
+        }
+    }
+
+    // Installed packages for the update tests
+    const STATUS: &str = "\
+Package: bash
+Status: install ok installed
+Architecture: amd64
+Version: 5.2.15-2
+
+Package: libc6
+Status: install ok installed
+Architecture: amd64
+Version: 2.36-9
+
+Package: libc6
+Status: install ok installed
+Architecture: i386
+Version: 2.36-9
+
+Package: nano
+Status: deinstall ok config-files
+Architecture: amd64
+Version: 7.2-1
+
+Package: openssl
+Status: install ok installed
+Architecture: amd64
+Version: 3.0.11-1~deb12u1
+
+Package: curl
+Status: install ok installed
+Architecture: amd64
+Version: 8.5.0-2~bpo12+1
+
+Package: vim
+Status: install ok installed
+Architecture: amd64
+Version: 2:9.0.1378-2
+";
+
+    fn stanza(package: &str, architecture: &str, version: &str) -> String {
+        format!("Package: {package}\nArchitecture: {architecture}\nVersion: {version}\nDescription: {package}\n\n")
+    }
+
+    fn gzip(data: &str) -> Vec<u8> {
+        let mut encoder: GzEncoder<Vec<u8>> = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(data.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    // An LZ4 frame holding `data` in a single stored block
+    fn lz4(data: &str) -> Vec<u8> {
+        let mut frame: Vec<u8> = vec![0x04, 0x22, 0x4d, 0x18, 0x60, 0x40, 0x82];
+        frame.extend_from_slice(&(data.len() as u32 | 0x8000_0000).to_le_bytes());
+        frame.extend_from_slice(data.as_bytes());
+        frame.extend_from_slice(&[0, 0, 0, 0]);
+        frame
+    }
+
+    #[test]
+    fn finds_updates_in_the_lists() {
+        let fixture: Fixture = Fixture::new();
+        let status: PathBuf = fixture.write("status", STATUS);
+        let main: &str = "deb.debian.org_debian_dists_bookworm_main_binary";
+        fixture.write(
+            &format!("lists/{main}-amd64_Packages"),
+            [
+                stanza("bash", "amd64", "5.2.15-2+b7"),
+                stanza("libc6", "amd64", "2.36-9"),
+                // Not installed anymore, only its configuration files are left
+                stanza("nano", "amd64", "7.2-1.1"),
+                stanza("vim", "amd64", "2:9.0.1378-2"),
+                stanza("zsh", "amd64", "5.9-4"),
+            ]
+            .concat(),
+        );
+        fixture.write(
+            &format!("lists/{main}-i386_Packages"),
+            stanza("libc6", "i386", "2.36-9"),
+        );
+        fixture.write(
+            "lists/deb.debian.org_debian_dists_bookworm-updates_main_binary-amd64_Packages.gz",
+            gzip(&stanza("libc6", "amd64", "2.36-9+deb12u4")),
+        );
+        // The same version as in bookworm-updates, which makes it a security update too
+        fixture.write(
+            "lists/security.debian.org_debian-security_dists_bookworm-security_main_binary-amd64_Packages.lz4",
+            lz4(&[
+                stanza("libc6", "amd64", "2.36-9+deb12u4"),
+                stanza("openssl", "amd64", "3.0.13-1~deb12u1"),
+            ]
+            .concat()),
+        );
+        // Backports are only installed from when asked to, but then they're upgraded too
+        let backports: &str = "deb.debian.org_debian_dists_bookworm-backports";
+        fixture.write(
+            &format!("lists/{backports}_InRelease"),
+            "Suite: bookworm-backports\nNotAutomatic: yes\nButAutomaticUpgrades: yes\n",
+        );
+        fixture.write(
+            &format!("lists/{backports}_main_binary-amd64_Packages"),
+            stanza("curl", "amd64", "8.5.0-2~bpo12+2"),
+        );
+        // Experimental never is
+        let experimental: &str = "deb.debian.org_debian_dists_experimental";
+        fixture.write(
+            &format!("lists/{experimental}_Release"),
+            "Suite: experimental\nNotAutomatic: yes\n",
+        );
+        fixture.write(
+            &format!("lists/{experimental}_main_binary-amd64_Packages"),
+            [
+                stanza("bash", "amd64", "5.2.21-2"),
+                stanza("vim", "amd64", "2:9.1.0-1"),
+            ]
+            .concat(),
+        );
+        // Lists that can't be read are skipped, and the other files aren't lists
+        fixture.write(
+            "lists/deb.debian.org_debian_dists_trixie_main_binary-amd64_Packages.xz",
+            [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00],
+        );
+        fixture.write(
+            &format!("lists/{main}-amd64_Packages.diff_Index"),
+            stanza("vim", "amd64", "2:9.9-1"),
+        );
+        fixture.write("lists/lock", "");
+        fixture.create_dir("lists/partial");
+
+        let updates: Updates = read_apt_updates(&status, &fixture.path().join("lists")).unwrap();
+        // bash, libc6 for amd64, openssl and curl
+        assert_eq!(updates.count, 4);
+        // libc6 and openssl
+        assert_eq!(updates.security, Some(2));
+    }
+
+    #[test]
+    fn finds_no_updates_in_empty_lists() {
+        let fixture: Fixture = Fixture::new();
+        let status: PathBuf = fixture.write("status", STATUS);
+        let lists: PathBuf = fixture.create_dir("lists");
+        let updates: Updates = read_apt_updates(&status, &lists).unwrap();
+        assert_eq!((updates.count, updates.security), (0, Some(0)));
+
+        assert!(read_apt_updates(&status, &fixture.path().join("missing")).is_err());
+        assert!(read_apt_updates(&fixture.path().join("missing"), &lists).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::daemon::package_managers::main::{
+    command_exists, command_output, version_word, PackageManager,
+};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Where Homebrew installs itself on Apple Silicon, on Intel Macs and on Linux
+const PREFIXES: &[&str] = &["/opt/homebrew", "/usr/local", "/home/linuxbrew/.linuxbrew"];
+
+pub(crate) struct Homebrew;
+
+impl PackageManager for Homebrew {
+    fn name(&self) -> &str {
+        "homebrew"
+    }
+
+    fn is_present(&self) -> bool {
+        command_exists("brew")
+    }
+
+    fn count(&self) -> Result<u64, String> {
+        if let Some(count) = get_prefixes()
+            .iter()
+            .find_map(|prefix| read_prefix(prefix).ok())
+        {
+            return Ok(count);
+        }
+        // Headers only show up on a terminal, but skip them anyway
+        let stdout: String = command_output("brew", &["list", "-1"])?;
+        Ok(stdout
+            .lines()
+            .filter(|line| !line.starts_with("==>"))
+            .count() as u64)
+    }
+
+    fn version(&self) -> Option<String> {
+        version_word("brew", &["--version"], 1)
+    }
+}
+
+// `brew shellenv` exports the prefix, which is the only way to find custom installations
+fn get_prefixes() -> Vec<PathBuf> {
+    let mut prefixes: Vec<PathBuf> = PREFIXES.iter().map(PathBuf::from).collect();
+    if let Some(prefix) = env::var_os("HOMEBREW_PREFIX") {
+        prefixes.insert(0, PathBuf::from(prefix));
+    }
+    prefixes
+}
+
+// Formulae get a directory in the Cellar and casks one in the Caskroom, each holding the
+// installed versions
+pub(crate) fn read_prefix(prefix: &Path) -> Result<u64, String> {
+    let formulae: u64 = count_directories(&prefix.join("Cellar"))?;
+    let casks: u64 = count_directories(&prefix.join("Caskroom")).unwrap_or(0);
+    Ok(formulae + casks)
+}
+
+fn count_directories(directory: &Path) -> Result<u64, String> {
+    let entries: fs::ReadDir = fs::read_dir(directory).map_err(|e| e.to_string())?;
+    let count: usize = entries
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter(|entry| entry.path().is_dir())
+        .count();
+    Ok(count as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    #[test]
+    fn counts_formulae_and_casks() {
+        let fixture: Fixture = Fixture::new();
+        fixture.create_dir("Cellar/git/2.45.2");
+        fixture.create_dir("Cellar/python@3.12/3.12.4");
+        fixture.create_dir("Cellar/.keepme");
+        fixture.write("Cellar/.DS_Store", "");
+        fixture.create_dir("Caskroom/firefox/127.0.2");
+        assert_eq!(read_prefix(fixture.path()), Ok(3));
+    }
+
+    #[test]
+    fn needs_a_cellar() {
+        // Linux prefixes often have no Caskroom, but without a Cellar it isn't Homebrew
+        let fixture: Fixture = Fixture::new();
+        fixture.create_dir("Cellar/git/2.45.2");
+        assert_eq!(read_prefix(fixture.path()), Ok(1));
+
+        let fixture: Fixture = Fixture::new();
+        fixture.create_dir("Caskroom/firefox/127.0.2");
+        assert!(read_prefix(fixture.path()).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use flate2::read::GzDecoder;
+use lz4_flex::frame::FrameDecoder;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+// Package metadata is stored plain, gzipped, or, where apt is told to keep its lists small, in
+// LZ4 frames. The format is told apart by its magic bytes rather than the file name, since
+// pacman's databases are gzipped tarballs named `.db`
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const LZ4_MAGIC: &[u8] = &[0x04, 0x22, 0x4d, 0x18];
+// Magic, flags, block size and header checksum, without any of the optional fields
+const LZ4_DESCRIPTOR_LENGTH: usize = 7;
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC: &[u8] = &[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00];
+
+pub(crate) fn read_decompressed(path: &Path) -> Result<Vec<u8>, String> {
+    let data: Vec<u8> = fs::read(path).map_err(|e| e.to_string())?;
+    if data.starts_with(GZIP_MAGIC) {
+        let mut decompressed: Vec<u8> = vec![];
+        GzDecoder::new(data.as_slice())
+            .read_to_end(&mut decompressed)
+            .map_err(|e| e.to_string())?;
+        return Ok(decompressed);
+    }
+    if data.starts_with(LZ4_MAGIC) {
+        // The decoder stops after a frame, but frames that follow each other make up one stream.
+        // Each one takes at least its descriptor, so this ends. The decoder takes a frame cut
+        // off right after its magic for the end of the stream, so that's caught here
+        let mut decompressed: Vec<u8> = vec![];
+        let mut decoder: FrameDecoder<&[u8]> = FrameDecoder::new(data.as_slice());
+        while !decoder.get_ref().is_empty() {
+            if decoder.get_ref().len() < LZ4_DESCRIPTOR_LENGTH {
+                return Err("truncated frame".to_string());
+            }
+            decoder
+                .read_to_end(&mut decompressed)
+                .map_err(|e| e.to_string())?;
+        }
+        return Ok(decompressed);
+    }
+    if data.starts_with(ZSTD_MAGIC) || data.starts_with(XZ_MAGIC) {
+        return Err("unsupported compression".to_string());
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{checked_in, Fixture};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    // What tests/fixtures/list.lz4 and random.lz4 hold
+    fn list() -> Vec<u8> {
+        (0..3000)
+            .map(|number| {
+                format!("Package: pkg{number}\nVersion: 1.{number}-1\nArchitecture: amd64\n\n")
+            })
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    fn random() -> Vec<u8> {
+        let mut state: u32 = 0x12345678;
+        (0..2000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decompresses_frames_from_the_lz4_tool() {
+        let fixture: Fixture = Fixture::new();
+        let list_frame: Vec<u8> = fs::read(checked_in("list.lz4")).unwrap();
+        let random_frame: Vec<u8> = fs::read(checked_in("random.lz4")).unwrap();
+        // Linked blocks with checksums and the content size
+        assert_eq!(read_decompressed(&checked_in("list.lz4")), Ok(list()));
+        // Stored blocks and a content checksum
+        assert_eq!(read_decompressed(&checked_in("random.lz4")), Ok(random()));
+        // Frames that follow each other make up one stream
+        assert_eq!(
+            read_decompressed(&fixture.write("both.lz4", [random_frame, list_frame].concat())),
+            Ok([random(), list()].concat())
+        );
+    }
+
+    #[test]
+    fn rejects_broken_frames() {
+        let fixture: Fixture = Fixture::new();
+        let frame: Vec<u8> = fs::read(checked_in("list.lz4")).unwrap();
+        let check = |name: &str, data: &[u8]| read_decompressed(&fixture.write(name, data));
+
+        // Cut off anywhere, in the descriptor, a block or the checksums
+        for length in [4, 6, 100, frame.len() / 2, frame.len() - 1] {
+            assert!(check("truncated.lz4", &frame[..length]).is_err());
+        }
+        assert!(check("truncated_second.lz4", &[&frame, LZ4_MAGIC].concat()).is_err());
+        // A flipped bit in the compressed data fails its block checksum
+        let mut corrupt: Vec<u8> = frame.clone();
+        corrupt[frame.len() / 2] ^= 0x01;
+        assert!(check("corrupt.lz4", &corrupt).is_err());
+        // Garbage after the magic, never decoding to anything
+        let mut state: u32 = 0x9e3779b9;
+        for round in 0..200 {
+            let garbage: Vec<u8> = (0..round * 5)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    state as u8
+                })
+                .collect();
+            assert!(check("garbage.lz4", &[LZ4_MAGIC, &garbage].concat()).is_err());
+        }
+    }
+
+    #[test]
+    fn tells_formats_apart_by_their_magic() {
+        let fixture: Fixture = Fixture::new();
+        let mut encoder: GzEncoder<Vec<u8>> = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&list()).unwrap();
+        let gzipped: Vec<u8> = encoder.finish().unwrap();
+
+        // Named like pacman's databases, the name doesn't matter
+        assert_eq!(
+            read_decompressed(&fixture.write("core.db", gzipped)),
+            Ok(list())
+        );
+        assert_eq!(read_decompressed(&checked_in("list.lz4")), Ok(list()));
+        assert_eq!(
+            read_decompressed(&fixture.write("Packages", list())),
+            Ok(list())
+        );
+        assert_eq!(read_decompressed(&fixture.write("empty", "")), Ok(vec![]));
+        for (name, magic) in [("Packages.zst", ZSTD_MAGIC), ("Packages.xz", XZ_MAGIC)] {
+            assert_eq!(
+                read_decompressed(&fixture.write(name, [magic, b"rest"].concat())),
+                Err("unsupported compression".to_string())
+            );
+        }
+        assert!(read_decompressed(&fixture.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::daemon::package_managers::main::{
+    command_exists, command_output, PackageManager, Updates,
+};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::{Command, Output};
+
+// Fedora moved the database under /usr, older releases still keep it in /var
+const RPM_DBS: &[&str] = &[
+    "/usr/lib/sysimage/rpm/rpmdb.sqlite",
+    "/var/lib/rpm/rpmdb.sqlite",
+];
+
+pub(crate) struct Dnf;
+
+impl PackageManager for Dnf {
+    fn name(&self) -> &str {
+        "dnf"
+    }
+
+    fn is_present(&self) -> bool {
+        command_exists("dnf")
+    }
+
+    // Older rpm keeps a Berkeley DB instead, which only rpm itself reads
+    fn count(&self) -> Result<u64, String> {
+        if let Some(count) = RPM_DBS
+            .iter()
+            .find_map(|path| read_rpm_db(Path::new(path)).ok())
+        {
+            return Ok(count);
+        }
+        let stdout: String = command_output("rpm", &["-qa"])?;
+        Ok(stdout.lines().count() as u64)
+    }
+
+    // The version ends the first line, like `4.14.0` or `dnf5 version 5.1.17`
+    fn version(&self) -> Option<String> {
+        let output: String = command_output("dnf", &["--version"]).ok()?;
+        Some(
+            output
+                .lines()
+                .next()?
+                .split_whitespace()
+                .last()?
+                .to_string(),
+        )
+    }
+
+    // Comparing repodata needs the installed versions out of rpm's binary headers, so dnf does
+    // the comparing. `--cacheonly` keeps it to the metadata it already has
+    fn updates(&self) -> Option<Updates> {
+        let output: Output = Command::new("dnf")
+            .args(["--cacheonly", "--quiet", "check-update"])
+            .output()
+            .ok()?;
+        // 100 means there are updates and 0 that there are none, anything else failed
+        let count: u64 = match output.status.code() {
+            Some(0) => 0,
+            Some(100) => parse_check_update(&String::from_utf8_lossy(&output.stdout)),
+            _ => return None,
+        };
+        let security: Option<u64> = command_output(
+            "dnf",
+            &[
+                "--cacheonly",
+                "--quiet",
+                "updateinfo",
+                "list",
+                "--updates",
+                "--security",
+            ],
+        )
+        .ok()
+        .map(|stdout| parse_advisories(&stdout));
+        Some(Updates { count, security })
+    }
+}
+
+// rpm keeps every installed package as a row of its `Packages` table, the other tables are
+// indexes into it. Opened read-only, so the daemon never gets in the way of rpm writing to it
+pub(crate) fn read_rpm_db(path: &Path) -> Result<u64, String> {
+    let connection: Connection =
+        Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| e.to_string())?;
+    let count: i64 = connection
+        .query_row("SELECT COUNT(*) FROM Packages", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    Ok(count as u64)
+}
+
+// A line per update, like `bash.x86_64  5.2.26-3.fc40  updates`, names too long for the column
+// push the rest onto an indented line. Packages that replace others are listed again after
+// `Obsoleting Packages`
+fn parse_check_update(stdout: &str) -> u64 {
+    stdout
+        .lines()
+        .take_while(|line| !line.starts_with("Obsoleting"))
+        .filter(|line| !line.is_empty() && !line.starts_with(char::is_whitespace))
+        .filter(|line| !line.starts_with("Security:"))
+        .count() as u64
+}
+
+// A line per advisory and package, like `FEDORA-2024-1a2b3c  Moderate/Sec.  bash-5.2.26-3.fc40.x86_64`,
+// a package fixed by several advisories counts once
+fn parse_advisories(stdout: &str) -> u64 {
+    let packages: BTreeSet<&str> = stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(2))
+        .filter_map(|nevra| nevra.rsplitn(3, '-').nth(2))
+        .collect();
+    packages.len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{checked_in, Fixture};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn counts_rpm_packages() {
+        assert_eq!(read_rpm_db(&checked_in("packages.sqlite")), Ok(3000));
+    }
+
+    #[test]
+    fn counts_rows_still_in_the_write_ahead_log() {
+        let fixture: Fixture = Fixture::new();
+        let path: PathBuf = fixture.path().join("rpmdb.sqlite");
+        let writer: Connection = Connection::open(&path).unwrap();
+        writer
+            .execute_batch(
+                "PRAGMA journal_mode=WAL;
+                PRAGMA wal_autocheckpoint=0;
+                CREATE TABLE Packages (hnum INTEGER PRIMARY KEY, blob BLOB);
+                INSERT INTO Packages (blob) VALUES (x'00'), (x'01'), (x'02');",
+            )
+            .unwrap();
+        assert!(fixture.path().join("rpmdb.sqlite-wal").exists());
+        assert_eq!(read_rpm_db(&path), Ok(3));
+    }
+
+    #[test]
+    fn rejects_broken_databases() {
+        let fixture: Fixture = Fixture::new();
+        let database: Vec<u8> = fs::read(checked_in("packages.sqlite")).unwrap();
+        let check = |name: &str, data: &[u8]| read_rpm_db(&fixture.write(name, data));
+
+        assert!(check("text", &b"Package: bash\n".repeat(10)).is_err());
+        assert!(check("empty", b"").is_err());
+        assert!(check("header", &database[..100]).is_err());
+        // The Packages table is rooted in page 3 of 512 bytes, its pages come after that
+        assert!(check("truncated", &database[..3 * 512]).is_err());
+        assert!(read_rpm_db(&fixture.path().join("missing")).is_err());
+
+        // Pointing the rightmost child of the Packages root back at itself makes the tree endless
+        let mut looping: Vec<u8> = database.clone();
+        looping[2 * 512 + 8..2 * 512 + 12].copy_from_slice(&3_u32.to_be_bytes());
+        assert!(check("looping", &looping).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+use crate::daemon::package_managers::main::{version_word, PackageManager};
+use std::fs;
+use std::path::Path;
+
+const PKG_DIR: &str = "/var/db/pkg/";
+
+pub(crate) struct Emerge;
+
+impl PackageManager for Emerge {
+    fn name(&self) -> &str {
+        "emerge"
+    }
+
+    fn is_present(&self) -> bool {
+        Path::new(PKG_DIR).is_dir()
+    }
+
+    fn count(&self) -> Result<u64, String> {
+        match fs::read_dir(PKG_DIR) {
+            Ok(entries) => {
+                let mut count = 0u64;
+                for entry in entries.filter_map(Result::ok) {
+                    if entry.path().is_dir() {
+                        count += fs::read_dir(entry.path()).map_or(0, |d| d.count() as u64);
+                    }
+                }
+                Ok(count)
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    // Portage prints itself, like `Portage 3.0.63 (python 3.12.3-final-0, ...)`
+    fn version(&self) -> Option<String> {
+        version_word("emerge", &["--version"], 1)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::daemon::package_managers::main::{
+    command_exists, command_output, get_user_homes, version_word, PackageManager,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SYSTEM_INSTALLATION: &str = "/var/lib/flatpak";
+const USER_INSTALLATION: &str = ".local/share/flatpak";
+
+// Flatpak installs either for the whole system or into a user's home, which are counted apart
+pub(crate) enum Flatpak {
+    System,
+    User,
+}
+
+impl Flatpak {
+    fn get_installations(&self) -> Vec<PathBuf> {
+        match self {
+            Flatpak::System => vec![PathBuf::from(SYSTEM_INSTALLATION)],
+            Flatpak::User => get_user_homes()
+                .iter()
+                .map(|(_, home)| home.join(USER_INSTALLATION))
+                .collect(),
+        }
+    }
+}
+
+impl PackageManager for Flatpak {
+    fn name(&self) -> &str {
+        match self {
+            Flatpak::System => "flatpak-system",
+            Flatpak::User => "flatpak-user",
+        }
+    }
+
+    fn is_present(&self) -> bool {
+        command_exists("flatpak")
+            && self
+                .get_installations()
+                .iter()
+                .any(|installation| installation.is_dir())
+    }
+
+    // Users' installations are summed up, since the daemon can't ask flatpak for other users
+    fn count(&self) -> Result<u64, String> {
+        let counts: Vec<u64> = self
+            .get_installations()
+            .iter()
+            .filter_map(|installation| read_installation(installation).ok())
+            .collect();
+        if !counts.is_empty() {
+            return Ok(counts.iter().sum());
+        }
+        match self {
+            Flatpak::System => {
+                let stdout: String =
+                    command_output("flatpak", &["list", "--system", "--columns=ref"])?;
+                Ok(stdout.lines().count() as u64)
+            }
+            Flatpak::User => Err("no readable installation".to_string()),
+        }
+    }
+
+    // Like `Flatpak 1.14.4`
+    fn version(&self) -> Option<String> {
+        version_word("flatpak", &["--version"], 1)
+    }
+}
+
+// Apps and runtimes are both refs, laid out like `app/org.mozilla.firefox/x86_64/stable`. The
+// deployed commit of each is linked as `active`, so half-installed refs don't count
+pub(crate) fn read_installation(directory: &Path) -> Result<u64, String> {
+    if !directory.is_dir() {
+        return Err("no installation".to_string());
+    }
+    let count: usize = ["app", "runtime"]
+        .iter()
+        .flat_map(|kind| get_subdirectories(&directory.join(kind)))
+        .flat_map(|id| get_subdirectories(&id))
+        .flat_map(|arch| get_subdirectories(&arch))
+        .filter(|branch| branch.join("active").exists())
+        .count();
+    Ok(count as u64)
+}
+
+fn get_subdirectories(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return vec![];
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
@@ -0,0 +1,252 @@
+use crate::config::daemon::DAEMON_CONFIG_OBJECT;
+use crate::daemon::collectors::main::{Collector, Refresh};
+use crate::daemon::package_managers::{
+    appimage, apt, brew, dnf, emerge, flatpak, nix, pacman, snap, winget, xbps,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// A package manager counts what it installed, it ends up under its name in `packages` and
+// `package_info`, but only when it's present at all, so that a missing manager and an empty one
+// differ
+pub(crate) trait PackageManager: Send + Sync {
+    fn name(&self) -> &str;
+    fn is_present(&self) -> bool;
+    fn count(&self) -> Result<u64, String>;
+
+    fn version(&self) -> Option<String> {
+        None
+    }
+
+    // Pending updates, only from metadata the package manager already synced, never by going
+    // online
+    fn updates(&self) -> Option<Updates> {
+        None
+    }
+}
+
+// What the package manager knows besides its count, under its name in `package_info`
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct PackageInfo {
+    pub(crate) version: Option<String>,
+    pub(crate) updates: Option<Updates>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Updates {
+    pub(crate) count: u64,
+    // Missing when the metadata doesn't say which updates fix security issues
+    pub(crate) security: Option<u64>,
+}
+
+pub(crate) fn build_package_managers() -> Vec<Box<dyn PackageManager>> {
+    vec![
+        Box::new(pacman::Pacman),
+        Box::new(winget::Winget),
+        Box::new(dnf::Dnf),
+        Box::new(apt::Apt),
+        Box::new(brew::Homebrew),
+        Box::new(emerge::Emerge),
+        Box::new(xbps::Xbps),
+        Box::new(flatpak::Flatpak::System),
+        Box::new(flatpak::Flatpak::User),
+        Box::new(snap::Snap),
+        Box::new(nix::Nix::new(nix::Profile::System)),
+        Box::new(nix::Nix::new(nix::Profile::User)),
+        Box::new(nix::Nix::new(nix::Profile::HomeManager)),
+        Box::new(appimage::AppImage),
+    ]
+}
+
+fn present_managers(managers: &[Box<dyn PackageManager>]) -> Vec<&dyn PackageManager> {
+    managers
+        .iter()
+        .filter(|manager| manager.is_present())
+        .map(|manager| manager.as_ref())
+        .collect()
+}
+
+// The counts are plain numbers, as configs have always added them up, a count that failed is 0
+pub(crate) struct PackagesCollector {
+    managers: Vec<Box<dyn PackageManager>>,
+}
+
+impl Default for PackagesCollector {
+    fn default() -> Self {
+        PackagesCollector {
+            managers: build_package_managers(),
+        }
+    }
+}
+
+impl Collector for PackagesCollector {
+    type Output = BTreeMap<String, u64>;
+
+    fn name(&self) -> &str {
+        "packages"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(60))
+    }
+
+    async fn collect(&self) -> BTreeMap<String, u64> {
+        present_managers(&self.managers)
+            .iter()
+            .map(|manager| (manager.name().to_string(), manager.count().unwrap_or(0)))
+            .collect()
+    }
+}
+
+pub(crate) struct PackageInfoCollector {
+    managers: Vec<Box<dyn PackageManager>>,
+    // Versions only change with an upgrade of the manager, so each is only asked once
+    versions: Mutex<BTreeMap<String, Option<String>>>,
+    // Comparing against all the synced metadata is slow, so it has its own, longer interval
+    updates: Mutex<Option<UpdatesCheck>>,
+}
+
+struct UpdatesCheck {
+    checked: Instant,
+    updates: BTreeMap<String, Option<Updates>>,
+}
+
+impl Default for PackageInfoCollector {
+    fn default() -> Self {
+        PackageInfoCollector {
+            managers: build_package_managers(),
+            versions: Mutex::new(BTreeMap::new()),
+            updates: Mutex::new(None),
+        }
+    }
+}
+
+impl Collector for PackageInfoCollector {
+    type Output = BTreeMap<String, PackageInfo>;
+
+    fn name(&self) -> &str {
+        "package_info"
+    }
+
+    fn refresh(&self) -> Refresh {
+        Refresh::Every(Duration::from_secs(60))
+    }
+
+    async fn collect(&self) -> BTreeMap<String, PackageInfo> {
+        self.get_package_info()
+    }
+}
+
+impl PackageInfoCollector {
+    fn get_package_info(&self) -> BTreeMap<String, PackageInfo> {
+        let present: Vec<&dyn PackageManager> = present_managers(&self.managers);
+        let updates: BTreeMap<String, Option<Updates>> = self.get_updates(&present);
+        let mut versions: MutexGuard<BTreeMap<String, Option<String>>> = self
+            .versions
+            .lock()
+            .expect("Failed to lock package manager versions");
+        present
+            .iter()
+            .map(|manager| {
+                let version: Option<String> = versions
+                    .entry(manager.name().to_string())
+                    .or_insert_with(|| manager.version())
+                    .clone();
+                let info: PackageInfo = PackageInfo {
+                    version,
+                    updates: updates.get(manager.name()).cloned().flatten(),
+                };
+                (manager.name().to_string(), info)
+            })
+            .collect()
+    }
+
+    fn get_updates(&self, managers: &[&dyn PackageManager]) -> BTreeMap<String, Option<Updates>> {
+        let mut last_check: MutexGuard<Option<UpdatesCheck>> =
+            self.updates.lock().expect("Failed to lock package updates");
+        let interval: Duration =
+            Duration::from_secs(DAEMON_CONFIG_OBJECT.packages.updates_interval_seconds);
+        match last_check.as_ref() {
+            Some(check) if check.checked.elapsed() < interval => check.updates.clone(),
+            _ => {
+                let updates: BTreeMap<String, Option<Updates>> = managers
+                    .iter()
+                    .map(|manager| (manager.name().to_string(), manager.updates()))
+                    .collect();
+                *last_check = Some(UpdatesCheck {
+                    checked: Instant::now(),
+                    updates: updates.clone(),
+                });
+                updates
+            }
+        }
+    }
+}
+
+// Whether an executable of that name is somewhere on the PATH
+pub(crate) fn command_exists(program: &str) -> bool {
+    let Some(path) = env::var_os("PATH") else {
+        return false;
+    };
+    env::split_paths(&path).any(|directory| {
+        let candidate: PathBuf = directory.join(program);
+        candidate.is_file() || candidate.with_extension("exe").is_file()
+    })
+}
+
+// Standard output of a command that succeeded
+pub(crate) fn command_output(program: &str, args: &[&str]) -> Result<String, String> {
+    let output: Output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err("non-zero exit".to_string());
+    }
+
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+// Most managers print their version as the nth word of the first line, like `apt 2.6.1 (amd64)`
+pub(crate) fn version_word(program: &str, args: &[&str], word: usize) -> Option<String> {
+    let output: String = command_output(program, args).ok()?;
+    let word: &str = output.lines().next()?.split_whitespace().nth(word)?;
+    Some(word.trim_start_matches('v').to_string())
+}
+
+// The daemon runs as a system service, so per-user installations are looked for in the home
+// directory of every account, along with the daemon's own
+pub(crate) fn get_user_homes() -> Vec<(String, PathBuf)> {
+    let mut users: Vec<(String, PathBuf)> = read_user_homes(Path::new("/etc/passwd"));
+    if let (Ok(name), Some(home)) = (env::var("USER"), env::var_os("HOME")) {
+        let home: PathBuf = PathBuf::from(home);
+        if !users.iter().any(|(_, known)| *known == home) {
+            users.push((name, home));
+        }
+    }
+    users
+}
+
+// The name is the first field and the home directory the sixth, service accounts often have `/`
+// or a home that doesn't exist
+pub(crate) fn read_user_homes(passwd: &Path) -> Vec<(String, PathBuf)> {
+    let passwd: String = fs::read_to_string(passwd).unwrap_or_default();
+    let mut users: Vec<(String, PathBuf)> = passwd
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            Some((fields.first()?.to_string(), PathBuf::from(fields.get(5)?)))
+        })
+        .filter(|(_, home)| home != Path::new("/") && home.is_dir())
+        .collect();
+    users.sort();
+    users.dedup();
+    users
+}
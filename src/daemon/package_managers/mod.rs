@@ -0,0 +1,13 @@
+pub(crate) mod appimage;
+pub(crate) mod apt;
+pub(crate) mod brew;
+pub(crate) mod compression;
+pub(crate) mod dnf;
+pub(crate) mod emerge;
+pub(crate) mod flatpak;
+pub(crate) mod main;
+pub(crate) mod nix;
+pub(crate) mod pacman;
+pub(crate) mod snap;
+pub(crate) mod winget;
+pub(crate) mod xbps;
@@ -0,0 +1,161 @@
+use crate::daemon::package_managers::main::{get_user_homes, version_word, PackageManager};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+const STORE: &str = "/nix/store";
+// Directories walked before giving up, far more than profiles go
+const MAX_DEPTH: usize = 16;
+
+// Nix installs into profiles: the system's, one or more per user, and home-manager's, which
+// keeps its packages in a `home-path` inside each generation
+pub(crate) enum Profile {
+    System,
+    User,
+    HomeManager,
+}
+
+pub(crate) struct Nix {
+    profile: Profile,
+    // Profiles resolve to store paths that never change, so each is only walked once
+    counts: Mutex<BTreeMap<PathBuf, u64>>,
+}
+
+impl Nix {
+    pub(crate) fn new(profile: Profile) -> Self {
+        Nix {
+            profile,
+            counts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Store paths of the profiles that exist, the same profile is often reachable from a link in
+    // the home directory and from /nix/var
+    fn get_profiles(&self) -> BTreeSet<PathBuf> {
+        let candidates: Vec<PathBuf> = match self.profile {
+            Profile::System => vec![
+                PathBuf::from("/run/current-system/sw"),
+                PathBuf::from("/nix/var/nix/profiles/default"),
+            ],
+            Profile::User => get_user_homes()
+                .into_iter()
+                .flat_map(|(user, home)| {
+                    vec![
+                        home.join(".nix-profile"),
+                        PathBuf::from(format!("/etc/profiles/per-user/{user}")),
+                        PathBuf::from(format!("/nix/var/nix/profiles/per-user/{user}/profile")),
+                    ]
+                })
+                .collect(),
+            Profile::HomeManager => get_user_homes()
+                .into_iter()
+                .flat_map(|(user, home)| {
+                    vec![
+                        home.join(".local/state/nix/profiles/home-manager/home-path"),
+                        PathBuf::from(format!(
+                            "/nix/var/nix/profiles/per-user/{user}/home-manager/home-path"
+                        )),
+                    ]
+                })
+                .collect(),
+        };
+        candidates
+            .iter()
+            .filter_map(|candidate| fs::canonicalize(candidate).ok())
+            .filter(|profile| profile.starts_with(STORE))
+            .collect()
+    }
+}
+
+impl PackageManager for Nix {
+    fn name(&self) -> &str {
+        match self.profile {
+            Profile::System => "nix-system",
+            Profile::User => "nix-user",
+            Profile::HomeManager => "home-manager",
+        }
+    }
+
+    fn is_present(&self) -> bool {
+        !self.get_profiles().is_empty()
+    }
+
+    fn count(&self) -> Result<u64, String> {
+        let mut counts: MutexGuard<BTreeMap<PathBuf, u64>> = self
+            .counts
+            .lock()
+            .expect("Failed to lock nix profile counts");
+        let profiles: BTreeSet<PathBuf> = self.get_profiles();
+        // Old generations would pile up otherwise
+        counts.retain(|profile, _| profiles.contains(profile));
+        // A profile that can't be read, like another user's behind a private home, is left out
+        // of the total rather than failing it, and tried again next time
+        let mut readable: Vec<u64> = vec![];
+        for profile in profiles {
+            match counts.get(&profile) {
+                Some(count) => readable.push(*count),
+                None => {
+                    if let Ok(count) = read_profile(&profile) {
+                        counts.insert(profile, count);
+                        readable.push(count);
+                    }
+                }
+            }
+        }
+        match readable.is_empty() {
+            true => Err("no readable profile".to_string()),
+            false => Ok(readable.iter().sum()),
+        }
+    }
+
+    // Like `nix (Nix) 2.18.1`
+    fn version(&self) -> Option<String> {
+        version_word("nix", &["--version"], 2)
+    }
+}
+
+// Profiles are built by linking the files of every package into one tree, whole directories
+// when only one package has them. The packages are the store paths those links lead into
+pub(crate) fn read_profile(profile: &Path) -> Result<u64, String> {
+    let mut packages: BTreeSet<PathBuf> = BTreeSet::new();
+    collect_store_paths(profile, &mut packages, 0)?;
+    Ok(packages.len() as u64)
+}
+
+fn collect_store_paths(
+    directory: &Path,
+    packages: &mut BTreeSet<PathBuf>,
+    depth: usize,
+) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        return Ok(());
+    }
+    let entries: fs::ReadDir = fs::read_dir(directory).map_err(|e| e.to_string())?;
+    for entry in entries.filter_map(Result::ok) {
+        let path: PathBuf = entry.path();
+        // nix-env keeps a manifest of the profile next to the packages
+        if depth == 0 && entry.file_name().to_string_lossy().starts_with("manifest.") {
+            continue;
+        }
+        match fs::read_link(&path) {
+            Ok(target) => {
+                if let Some(package) = get_store_path(&target) {
+                    packages.insert(package);
+                }
+            }
+            Err(_) if path.is_dir() => collect_store_paths(&path, packages, depth + 1)?,
+            Err(_) => {}
+        }
+    }
+    Ok(())
+}
+
+// The first component below the store, like `/nix/store/<hash>-firefox-125.0.3`
+fn get_store_path(target: &Path) -> Option<PathBuf> {
+    let relative: &Path = target.strip_prefix(STORE).ok()?;
+    match relative.components().next()? {
+        Component::Normal(name) => Some(Path::new(STORE).join(name)),
+        _ => None,
+    }
+}
@@ -0,0 +1,478 @@
+use crate::daemon::package_managers::compression::read_decompressed;
+use crate::daemon::package_managers::main::{
+    command_exists, command_output, PackageManager, Updates,
+};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LOCAL_DB: &str = "/var/lib/pacman/local";
+const SYNC_DB: &str = "/var/lib/pacman/sync";
+const PACMAN_CONF: &str = "/etc/pacman.conf";
+const TAR_BLOCK: usize = 512;
+
+pub(crate) struct Pacman;
+
+impl PackageManager for Pacman {
+    fn name(&self) -> &str {
+        "pacman"
+    }
+
+    fn is_present(&self) -> bool {
+        command_exists("pacman")
+    }
+
+    fn count(&self) -> Result<u64, String> {
+        read_local_db(Path::new(LOCAL_DB)).or_else(|_| {
+            let stdout: String = command_output("pacman", &["-Q"])?;
+            Ok(stdout.lines().count() as u64)
+        })
+    }
+
+    // The version sits next to the logo, like `Pacman v6.1.0 - libalpm v14.0.0`
+    fn version(&self) -> Option<String> {
+        let output: String = command_output("pacman", &["-V"]).ok()?;
+        let (_, version) = output.split_once("Pacman v")?;
+        Some(version.split_whitespace().next()?.to_string())
+    }
+
+    fn updates(&self) -> Option<Updates> {
+        let repositories: Vec<String> = read_repositories(Path::new(PACMAN_CONF));
+        read_pacman_updates(Path::new(LOCAL_DB), Path::new(SYNC_DB), &repositories).ok()
+    }
+}
+
+// A directory per installed package, like `bash-5.2.026-2`, next to the `ALPM_DB_VERSION` file
+pub(crate) fn read_local_db(directory: &Path) -> Result<u64, String> {
+    let entries: fs::ReadDir = fs::read_dir(directory).map_err(|e| e.to_string())?;
+    let count: usize = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().join("desc").is_file())
+        .count();
+    Ok(count as u64)
+}
+
+// Repositories in the order pacman.conf lists them, which is the order pacman picks packages
+// from. Each is a section, except for `[options]`
+pub(crate) fn read_repositories(conf: &Path) -> Vec<String> {
+    let conf: String = fs::read_to_string(conf).unwrap_or_default();
+    conf.lines()
+        .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']'))
+        .filter(|section| *section != "options")
+        .map(str::to_string)
+        .collect()
+}
+
+// Like `pacman -Qu`, a package can be upgraded when the first repository that has it has a newer
+// version. Arch doesn't say which updates fix security issues
+pub(crate) fn read_pacman_updates(
+    local: &Path,
+    sync: &Path,
+    repositories: &[String],
+) -> Result<Updates, String> {
+    let repositories: Vec<BTreeMap<String, String>> = repositories
+        .iter()
+        .filter_map(|repository| read_sync_db(&sync.join(format!("{repository}.db"))).ok())
+        .collect();
+    if repositories.is_empty() {
+        return Err("no sync databases".to_string());
+    }
+
+    let mut count: u64 = 0;
+    for entry in fs::read_dir(local).map_err(|e| e.to_string())? {
+        let path: PathBuf = entry.map_err(|e| e.to_string())?.path();
+        let Ok(desc) = fs::read_to_string(path.join("desc")) else {
+            continue;
+        };
+        let (Some(name), Some(version)) = parse_desc(&desc) else {
+            continue;
+        };
+        let newer: bool = repositories
+            .iter()
+            .find_map(|repository| repository.get(name))
+            .is_some_and(|available| compare_versions(available, version) == Ordering::Greater);
+        count += newer as u64;
+    }
+    Ok(Updates {
+        count,
+        security: None,
+    })
+}
+
+// Sync databases are tarballs with a directory per package, whose `desc` is like the local one
+fn read_sync_db(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let tarball: Vec<u8> = read_decompressed(path)?;
+    let mut packages: BTreeMap<String, String> = BTreeMap::new();
+    let mut position: usize = 0;
+    // Every entry is a header block and its contents padded to whole blocks, two empty blocks
+    // end the archive
+    while let Some(header) = tarball.get(position..position + TAR_BLOCK) {
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+        let name: String = String::from_utf8_lossy(
+            header[..100]
+                .split(|byte| *byte == 0)
+                .next()
+                .unwrap_or_default(),
+        )
+        .to_string();
+        // The size is octal text, padded with zeros or spaces
+        let size: &str = std::str::from_utf8(&header[124..136])
+            .map_err(|e| e.to_string())?
+            .trim_matches(|character: char| character == '\0' || character == ' ');
+        let size: usize = usize::from_str_radix(size, 8).map_err(|e| e.to_string())?;
+        let contents: &[u8] = tarball
+            .get(position + TAR_BLOCK..position + TAR_BLOCK + size)
+            .ok_or("truncated archive")?;
+        if name.ends_with("/desc") {
+            if let (Some(name), Some(version)) = parse_desc(&String::from_utf8_lossy(contents)) {
+                packages.insert(name.to_string(), version.to_string());
+            }
+        }
+        position += TAR_BLOCK + size.div_ceil(TAR_BLOCK) * TAR_BLOCK;
+    }
+    Ok(packages)
+}
+
+// Fields are a `%NAME%` line followed by the values, one per line, up to a blank line
+fn parse_desc(desc: &str) -> (Option<&str>, Option<&str>) {
+    let field = |key: &str| -> Option<&str> {
+        let mut lines = desc.lines();
+        lines.find(|line| *line == key)?;
+        lines.next().filter(|value| !value.is_empty())
+    };
+    (field("%NAME%"), field("%VERSION%"))
+}
+
+// Versions are `epoch:version-release`, where the epoch is optional
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let split = |version: &str| -> (String, String, Option<String>) {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) if epoch.chars().all(|c| c.is_ascii_digit()) => {
+                (epoch.to_string(), rest)
+            }
+            _ => ("0".to_string(), version),
+        };
+        match rest.rsplit_once('-') {
+            Some((version, release)) => (epoch, version.to_string(), Some(release.to_string())),
+            None => (epoch, rest.to_string(), None),
+        }
+    };
+    let (a_epoch, a_version, a_release) = split(a);
+    let (b_epoch, b_version, b_release) = split(b);
+    compare_segments(&a_epoch, &b_epoch)
+        .then_with(|| compare_segments(&a_version, &b_version))
+        .then_with(|| match (a_release, b_release) {
+            (Some(a_release), Some(b_release)) => compare_segments(&a_release, &b_release),
+            _ => Ordering::Equal,
+        })
+}
+
+// rpm's algorithm, as pacman has it: runs of digits or letters are compared in turn, numbers
+// numerically and beating letters. More separators before a run make it newer, and a trailing
+// run of letters makes it older, like `1.0alpha` before `1.0`
+fn compare_segments(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (a, b): (&[u8], &[u8]) = (a.as_bytes(), b.as_bytes());
+    let (mut one, mut two): (usize, usize) = (0, 0);
+    while one < a.len() && two < b.len() {
+        let (separator_one, separator_two): (usize, usize) = (one, two);
+        while one < a.len() && !a[one].is_ascii_alphanumeric() {
+            one += 1;
+        }
+        while two < b.len() && !b[two].is_ascii_alphanumeric() {
+            two += 1;
+        }
+        if one >= a.len() || two >= b.len() {
+            break;
+        }
+        if one - separator_one != two - separator_two {
+            return (one - separator_one).cmp(&(two - separator_two));
+        }
+
+        let is_number: bool = a[one].is_ascii_digit();
+        let run = |text: &[u8], start: usize| -> usize {
+            let mut end: usize = start;
+            while end < text.len()
+                && match is_number {
+                    true => text[end].is_ascii_digit(),
+                    false => text[end].is_ascii_alphabetic(),
+                }
+            {
+                end += 1;
+            }
+            end
+        };
+        let (end_one, end_two): (usize, usize) = (run(a, one), run(b, two));
+        // A number against letters, the number wins
+        if end_two == two {
+            return match is_number {
+                true => Ordering::Greater,
+                false => Ordering::Less,
+            };
+        }
+        let (mut segment_one, mut segment_two): (&[u8], &[u8]) =
+            (&a[one..end_one], &b[two..end_two]);
+        if is_number {
+            while segment_one.first() == Some(&b'0') {
+                segment_one = &segment_one[1..];
+            }
+            while segment_two.first() == Some(&b'0') {
+                segment_two = &segment_two[1..];
+            }
+            let length: Ordering = segment_one.len().cmp(&segment_two.len());
+            if length != Ordering::Equal {
+                return length;
+            }
+        }
+        let ordering: Ordering = segment_one.cmp(segment_two);
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        one = end_one;
+        two = end_two;
+    }
+    if one >= a.len() && two >= b.len() {
+        return Ordering::Equal;
+    }
+    // Whatever is left over decides, but letters never beat the end of the other version
+    match (one >= a.len() && !b[two].is_ascii_alphabetic())
+        || (one < a.len() && a[one].is_ascii_alphabetic())
+    {
+        true => Ordering::Less,
+        false => Ordering::Greater,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn counts_installed_packages() {
+        let fixture: Fixture = Fixture::new();
+        fixture.write("local/ALPM_DB_VERSION", "9\n");
+        fixture.write("local/bash-5.2.026-2/desc", "%NAME%\nbash\n");
+        fixture.write("local/bash-5.2.026-2/files", "%FILES%\nusr/bin/bash\n");
+        fixture.write("local/linux-6.9.7.arch1-1/desc", "%NAME%\nlinux\n");
+        // Left behind by an interrupted transaction, without its desc
+        fixture.write("local/glibc-2.39-1/files", "");
+        let local: PathBuf = fixture.path().join("local");
+        assert_eq!(read_local_db(&local), Ok(2));
+
+        assert_eq!(read_local_db(&fixture.create_dir("empty")), Ok(0));
+        assert!(read_local_db(&fixture.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn compares_versions_like_vercmp() {
+        // From pacman's own tests for vercmp
+        let cases: &[(&str, &str, Ordering)] = &[
+            ("1.5.0", "1.5.0", Ordering::Equal),
+            ("1.5.1", "1.5.0", Ordering::Greater),
+            ("1.5.1", "1.5", Ordering::Greater),
+            // Releases
+            ("1.5.0-1", "1.5.0-1", Ordering::Equal),
+            ("1.5.0-1", "1.5.0-2", Ordering::Less),
+            ("1.5.0-1", "1.5.1-1", Ordering::Less),
+            ("1.5.0-2", "1.5.1-1", Ordering::Less),
+            ("1.5-1", "1.5.1-1", Ordering::Less),
+            ("1.5-2", "1.5.1-2", Ordering::Less),
+            // Left out on one side, the release doesn't count
+            ("1.5", "1.5-1", Ordering::Equal),
+            ("1.1-1", "1.1", Ordering::Equal),
+            ("1.0-1", "1.1", Ordering::Less),
+            ("1.1-1", "1.0", Ordering::Greater),
+            // Letters
+            ("1.5b-1", "1.5-1", Ordering::Less),
+            ("1.5b", "1.5", Ordering::Less),
+            ("1.5b", "1.5.1", Ordering::Less),
+            ("1.0a", "1.0alpha", Ordering::Less),
+            ("1.0alpha", "1.0b", Ordering::Less),
+            ("1.0b", "1.0beta", Ordering::Less),
+            ("1.0beta", "1.0rc", Ordering::Less),
+            ("1.0rc", "1.0", Ordering::Less),
+            ("1.5.a", "1.5", Ordering::Greater),
+            ("1.5.b", "1.5.a", Ordering::Greater),
+            ("1.5.1", "1.5.b", Ordering::Greater),
+            ("1.5.b-1", "1.5.b", Ordering::Equal),
+            ("1.5-1", "1.5.b", Ordering::Less),
+            // Separators
+            ("2.0", "2_0", Ordering::Equal),
+            ("2.0_a", "2_0.a", Ordering::Equal),
+            ("2.0a", "2.0.a", Ordering::Less),
+            ("2___a", "2_a", Ordering::Greater),
+            // Epochs
+            ("0:1.0", "0:1.0", Ordering::Equal),
+            ("0:1.0", "0:1.1", Ordering::Less),
+            ("1:1.0", "0:1.0", Ordering::Greater),
+            ("1:1.0", "0:1.1", Ordering::Greater),
+            ("1:1.0", "2:1.1", Ordering::Less),
+            ("1:1.0", "0:1.0-1", Ordering::Greater),
+            ("1:1.0-1", "0:1.1-1", Ordering::Greater),
+            ("0:1.0", "1.0", Ordering::Equal),
+            ("0:1.1", "1.0", Ordering::Greater),
+            ("1:1.0", "1.1", Ordering::Greater),
+            // Snapshots
+            ("1.0.r123.gabcdef-1", "1.0.r124.g123456-1", Ordering::Less),
+            ("6.9.7.arch1-1", "6.9.10.arch1-1", Ordering::Less),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare_versions(a, b), *expected, "{a} against {b}");
+            assert_eq!(
+                compare_versions(b, a),
+                expected.reverse(),
+                "{b} against {a}"
+            );
+        }
+    }
+
+    fn desc(name: &str, version: &str) -> String {
+        format!(
+            "%FILENAME%\n{name}-{version}-x86_64.pkg.tar.zst\n\n%NAME%\n{name}\n\n%VERSION%\n{version}\n\n%DESC%\n{name}\n\n"
+        )
+    }
+
+    // A ustar archive of a directory per package with its desc, like the sync databases
+    fn sync_db(packages: &[(&str, &str)]) -> Vec<u8> {
+        let mut tarball: Vec<u8> = vec![];
+        let mut add = |name: String, kind: u8, contents: &[u8]| {
+            let mut header: Vec<u8> = vec![0; TAR_BLOCK];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[100..107].copy_from_slice(b"0000644");
+            header[124..135].copy_from_slice(format!("{:011o}", contents.len()).as_bytes());
+            header[156] = kind;
+            header[257..263].copy_from_slice(b"ustar\0");
+            // The checksum is the sum of the header with its own field as spaces
+            header[148..156].copy_from_slice(b"        ");
+            let checksum: u32 = header.iter().map(|byte| *byte as u32).sum();
+            header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+            tarball.extend_from_slice(&header);
+            tarball.extend_from_slice(contents);
+            tarball.resize(tarball.len().div_ceil(TAR_BLOCK) * TAR_BLOCK, 0);
+        };
+        for (name, version) in packages {
+            add(format!("{name}-{version}/"), b'5', b"");
+            add(
+                format!("{name}-{version}/desc"),
+                b'0',
+                desc(name, version).as_bytes(),
+            );
+        }
+        tarball.extend_from_slice(&[0; 2 * TAR_BLOCK]);
+        tarball
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder: GzEncoder<Vec<u8>> = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn install(fixture: &Fixture, name: &str, version: &str) {
+        fixture.write(&format!("local/{name}-{version}/desc"), desc(name, version));
+    }
+
+    #[test]
+    fn reads_repositories_in_order() {
+        let fixture: Fixture = Fixture::new();
+        let conf: &str = "\
+[options]
+HoldPkg     = pacman glibc
+Architecture = auto
+
+#[core-testing]
+#Include = /etc/pacman.d/mirrorlist
+
+[core]
+Include = /etc/pacman.d/mirrorlist
+
+[extra]
+Include = /etc/pacman.d/mirrorlist
+
+  [custom]
+Server = file:///home/custompkgs
+";
+        let conf: PathBuf = fixture.write("pacman.conf", conf);
+        assert_eq!(read_repositories(&conf), ["core", "extra", "custom"]);
+        assert!(read_repositories(&fixture.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn finds_updates_in_the_sync_databases() {
+        let fixture: Fixture = Fixture::new();
+        fixture.write("local/ALPM_DB_VERSION", "9\n");
+        install(&fixture, "bash", "5.2.026-2");
+        install(&fixture, "glibc", "2.39-1");
+        install(&fixture, "linux", "6.9.7.arch1-1");
+        install(&fixture, "firefox", "127.0.2-1");
+        // Built from the AUR, no repository has it
+        install(&fixture, "yay", "12.3.5-1");
+
+        fixture.write(
+            "sync/core.db",
+            gzip(&sync_db(&[
+                ("bash", "5.2.026-5"),
+                ("glibc", "2.39-1"),
+                ("linux", "6.9.10.arch1-1"),
+            ])),
+        );
+        fixture.write(
+            "sync/extra.db",
+            gzip(&sync_db(&[("firefox", "128.0-1"), ("yay", "12.3.0-1")])),
+        );
+        // Only used for packages the repositories before it don't have
+        fixture.write(
+            "sync/core-testing.db",
+            sync_db(&[("glibc", "2.40-1"), ("yay", "12.4.0-1")]),
+        );
+        fixture.write("sync/extra.files", gzip(&sync_db(&[("bash", "9.9-1")])));
+
+        let repositories: Vec<String> = ["core", "extra", "multilib", "core-testing"]
+            .iter()
+            .map(|repository| repository.to_string())
+            .collect();
+        let updates: Updates = read_pacman_updates(
+            &fixture.path().join("local"),
+            &fixture.path().join("sync"),
+            &repositories,
+        )
+        .unwrap();
+        // bash, linux and firefox
+        assert_eq!(updates.count, 3);
+        assert_eq!(updates.security, None);
+    }
+
+    #[test]
+    fn needs_a_sync_database() {
+        let fixture: Fixture = Fixture::new();
+        install(&fixture, "bash", "5.2.026-2");
+        // Cut off in the middle of a desc
+        let tarball: Vec<u8> = sync_db(&[("bash", "5.2.026-5")]);
+        fixture.write("sync/core.db", &tarball[..TAR_BLOCK * 2 + 10]);
+        assert_eq!(
+            read_sync_db(&fixture.path().join("sync/core.db")),
+            Err("truncated archive".to_string())
+        );
+        assert_eq!(
+            read_pacman_updates(
+                &fixture.path().join("local"),
+                &fixture.path().join("sync"),
+                &["core".to_string(), "extra".to_string()],
+            )
+            .map(|updates| updates.count),
+            Err("no sync databases".to_string())
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use crate::daemon::package_managers::main::{
+    command_exists, command_output, version_word, PackageManager,
+};
+use std::fs;
+use std::path::Path;
+
+// Fedora and Arch mount snaps under /var, where the FHS wants nothing new in /
+const SNAP_DIRECTORIES: &[&str] = &["/snap", "/var/lib/snapd/snap"];
+
+pub(crate) struct Snap;
+
+impl PackageManager for Snap {
+    fn name(&self) -> &str {
+        "snap"
+    }
+
+    fn is_present(&self) -> bool {
+        command_exists("snap")
+    }
+
+    fn count(&self) -> Result<u64, String> {
+        if let Some(count) = SNAP_DIRECTORIES
+            .iter()
+            .find_map(|directory| read_snap_directory(Path::new(directory)).ok())
+        {
+            return Ok(count);
+        }
+        let stdout: String = command_output("snap", &["list"])?;
+        // The first line is the header
+        Ok((stdout.lines().count() as u64).saturating_sub(1))
+    }
+
+    // The first line names the client, like `snap    2.61.3+22.04`
+    fn version(&self) -> Option<String> {
+        version_word("snap", &["--version"], 1)
+    }
+}
+
+// Every snap mounts its revisions in a directory of its own and links the one in use as
+// `current`, next to a `bin` directory of wrappers
+pub(crate) fn read_snap_directory(directory: &Path) -> Result<u64, String> {
+    let entries: fs::ReadDir = fs::read_dir(directory).map_err(|e| e.to_string())?;
+    let count: usize = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().join("current").exists())
+        .count();
+    Ok(count as u64)
+}
@@ -0,0 +1,26 @@
+use crate::daemon::package_managers::main::{
+    command_exists, command_output, version_word, PackageManager,
+};
+
+pub(crate) struct Winget;
+
+impl PackageManager for Winget {
+    fn name(&self) -> &str {
+        "winget"
+    }
+
+    fn is_present(&self) -> bool {
+        command_exists("winget")
+    }
+
+    fn count(&self) -> Result<u64, String> {
+        let stdout: String = command_output("winget", &["list"])?;
+        let count: u64 = stdout.lines().count() as u64;
+
+        Ok(count)
+    }
+
+    fn version(&self) -> Option<String> {
+        version_word("winget", &["--version"], 0)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::daemon::package_managers::main::{
+    command_exists, command_output, version_word, PackageManager,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const XBPS_DB: &str = "/var/db/xbps";
+
+pub(crate) struct Xbps;
+
+impl PackageManager for Xbps {
+    fn name(&self) -> &str {
+        "xbps"
+    }
+
+    fn is_present(&self) -> bool {
+        command_exists("xbps-query")
+    }
+
+    fn count(&self) -> Result<u64, String> {
+        read_pkgdb(Path::new(XBPS_DB)).or_else(|_| {
+            let stdout: String = command_output("xbps-query", &["-l"])?;
+            Ok(stdout.lines().count() as u64)
+        })
+    }
+
+    // Like `XBPS: 0.59.2 API: 20200423 ...`
+    fn version(&self) -> Option<String> {
+        version_word("xbps-query", &["-V"], 1)
+    }
+}
+
+// The database is a property list named after its format version, like `pkgdb-0.38.plist`, a
+// dictionary keyed by package name whose entries each have a `state`
+pub(crate) fn read_pkgdb(directory: &Path) -> Result<u64, String> {
+    let entries: fs::ReadDir = fs::read_dir(directory).map_err(|e| e.to_string())?;
+    let path: PathBuf = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("pkgdb-") && name.ends_with(".plist"))
+        })
+        .ok_or("no pkgdb")?;
+    let plist: String = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(count_installed(&plist))
+}
+
+// Packages are the dictionaries one level into the top one, their `state` is one of
+// `installed`, `unpacked`, `half-removed` and so on
+fn count_installed(plist: &str) -> u64 {
+    let mut depth: usize = 0;
+    let mut key: &str = "";
+    let mut count: u64 = 0;
+    for element in plist.split('<').skip(1) {
+        let (tag, text) = element.split_once('>').unwrap_or((element, ""));
+        match tag {
+            "dict" => depth += 1,
+            "/dict" => depth = depth.saturating_sub(1),
+            "key" => key = text,
+            "string" if depth == 2 && key == "state" && text == "installed" => count += 1,
+            _ => {}
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Fixture;
+
+    #[test]
+    fn counts_installed_packages() {
+        let fixture: Fixture = Fixture::new();
+        let plist: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple Computer//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>_XBPS_ALTERNATIVES_</key>
+	<dict>
+		<key>sh</key>
+		<array>
+			<string>bash</string>
+		</array>
+	</dict>
+	<key>bash</key>
+	<dict>
+		<key>pkgver</key>
+		<string>bash-5.2.21_1</string>
+		<key>run_depends</key>
+		<array>
+			<dict>
+				<key>state</key>
+				<string>installed</string>
+			</dict>
+		</array>
+		<key>state</key>
+		<string>installed</string>
+	</dict>
+	<key>linux6.6</key>
+	<dict>
+		<key>state</key>
+		<string>installed</string>
+	</dict>
+	<key>vim</key>
+	<dict>
+		<key>state</key>
+		<string>unpacked</string>
+	</dict>
+</dict>
+</plist>
+"#;
+        fixture.write("db/pkgdb-0.38.plist", plist);
+        fixture.write("db/.xbps-pkgdb.lock", "");
+        fixture.write("db/void-repo-nonfree-files.plist", "");
+        let db: PathBuf = fixture.path().join("db");
+        // Nested dictionaries don't count, only the packages' own state
+        assert_eq!(read_pkgdb(&db), Ok(2));
+
+        assert_eq!(
+            read_pkgdb(&fixture.create_dir("empty")),
+            Err("no pkgdb".to_string())
+        );
+    }
+}
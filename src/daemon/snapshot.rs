@@ -0,0 +1,93 @@
+use crate::daemon::fetch_info::serialize_fetch;
+use crate::STATE_PATH;
+use serde_yaml::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CURRENT_SNAPSHOT: &str = "current.yaml";
+const PREVIOUS_BOOT_SNAPSHOT: &str = "previous_boot.yaml";
+// The boot time is derived from the uptime on some platforms, so it can drift by a few seconds
+const BOOT_TIME_TOLERANCE: u64 = 10;
+// Lists the fields the collectors declared volatile, so that a diff knows which to leave out
+pub(crate) const VOLATILE_FIELDS_KEY: &str = "volatile_fields";
+
+pub(crate) fn get_current_snapshot_location() -> PathBuf {
+    Path::new(&*STATE_PATH).join(CURRENT_SNAPSHOT)
+}
+
+pub(crate) fn get_previous_boot_snapshot_location() -> PathBuf {
+    Path::new(&*STATE_PATH).join(PREVIOUS_BOOT_SNAPSHOT)
+}
+
+// The snapshot left behind by the last run becomes the previous boot snapshot, but only if it was
+// taken during another boot, otherwise a daemon restart would throw away the real one
+pub(crate) fn rotate_boot_snapshot(boot_time: u64) -> Result<(), String> {
+    let current: PathBuf = get_current_snapshot_location();
+    let serialized: String = match fs::read_to_string(&current) {
+        Ok(serialized) => serialized,
+        Err(_) => return Ok(()),
+    };
+    let snapshot: Value = serde_yaml::from_str(&serialized).map_err(|e| e.to_string())?;
+    let snapshot_boot_time: u64 = snapshot
+        .get("boot_time")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if snapshot_boot_time.abs_diff(boot_time) > BOOT_TIME_TOLERANCE {
+        fs::rename(&current, get_previous_boot_snapshot_location()).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn save_current_snapshot() {
+    if let Err(e) = write_snapshot(&serialize_fetch()) {
+        eprintln!("Failed to save snapshot to {}: {}", STATE_PATH.as_str(), e);
+    }
+}
+
+fn write_snapshot(serialized: &str) -> Result<(), String> {
+    fs::create_dir_all(&*STATE_PATH).map_err(|e| e.to_string())?;
+    // Write next to the real file and swap it in, so a reader never sees half a snapshot
+    let current: PathBuf = get_current_snapshot_location();
+    let temporary: PathBuf = current.with_extension("yaml.tmp");
+    fs::write(&temporary, serialized).map_err(|e| e.to_string())?;
+    fs::rename(&temporary, &current).map_err(|e| e.to_string())
+}
+
+// Paths look like `memory.used` or `disks[/].total`, where the brackets pick the list entry with
+// that name, or failing that, the entry at that index
+pub(crate) fn lookup<'a>(snapshot: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut current: &Value = snapshot;
+    let mut rest: &str = path;
+    while !rest.is_empty() {
+        if let Some(selector) = rest.strip_prefix('[') {
+            let end: usize = selector.find(']')?;
+            let name: &str = &selector[..end];
+            let entries: &[Value] = current.as_sequence()?;
+            current = entries
+                .iter()
+                .find(|entry| entry.get("name").map(render_value).as_deref() == Some(name))
+                .or_else(|| entries.get(name.parse::<usize>().ok()?))?;
+            rest = &selector[end + 1..];
+        } else {
+            let end: usize = rest.find(['.', '[']).unwrap_or(rest.len());
+            current = current.get(&rest[..end])?;
+            rest = &rest[end..];
+        }
+        rest = rest.strip_prefix('.').unwrap_or(rest);
+    }
+    Some(current)
+}
+
+pub(crate) fn render_value(value: &Value) -> String {
+    match value {
+        Value::Null => "none".to_string(),
+        Value::Bool(bool) => bool.to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::String(string) => string.clone(),
+        Value::Tagged(tagged) => render_value(&tagged.value),
+        Value::Sequence(_) | Value::Mapping(_) => serde_yaml::to_string(value)
+            .unwrap_or_default()
+            .trim()
+            .replace('\n', ", "),
+    }
+}
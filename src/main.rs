@@ -2,45 +2,94 @@ mod ascii_art;
 mod client;
 mod config;
 mod daemon;
+#[cfg(test)]
+mod testing;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use lazy_static::lazy_static;
 
 #[derive(Parser, Debug)]
 struct Args {
     #[arg(long, short, help = "Run as daemon")]
     daemon: bool,
-    #[arg(long, short, help = "Set the socket path for the client or daemon")]
+    #[arg(
+        long,
+        short,
+        global = true,
+        help = "Set the socket path for the client or daemon"
+    )]
     socket_path: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        help = "Set the directory the daemon stores its snapshots in, for the client or daemon"
+    )]
+    state_path: Option<String>,
     #[arg(
         long,
         short,
         help = "On exit print the execution time, for benchmarking"
     )]
     benchmark: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(about = "Report what changed between two snapshots")]
+    Diff {
+        #[arg(
+            default_value = "boot",
+            help = "A snapshot file, \"boot\" for the previous boot or \"now\" for the daemon"
+        )]
+        snapshot_a: String,
+        #[arg(
+            default_value = "now",
+            help = "A snapshot file, \"boot\" for the previous boot or \"now\" for the daemon"
+        )]
+        snapshot_b: String,
+        #[arg(long, short, help = "Also report values that change constantly")]
+        all: bool,
+    },
 }
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 lazy_static! {
     static ref SOCKET_PATH: String = parse_path().unwrap_or("/tmp/hayabusa".to_string());
+    static ref STATE_PATH: String = parse_state_path().unwrap_or("/var/lib/hayabusa".to_string());
 }
 
 #[cfg(target_os = "windows")]
 lazy_static! {
     static ref SOCKET_PATH: String = parse_path().unwrap_or("hayabusa".to_string());
+    static ref STATE_PATH: String = parse_state_path().unwrap_or(
+        std::env::var("PROGRAMDATA").unwrap_or("C:\\ProgramData".to_string()) + "\\hayabusa"
+    );
 }
 fn parse_path() -> Option<String> {
     Args::parse().socket_path
 }
 
+fn parse_state_path() -> Option<String> {
+    Args::parse().state_path
+}
+
 #[tokio::main]
 async fn main() {
     let start: std::time::Instant = std::time::Instant::now();
     let args: Args = Args::parse();
-    match args.daemon {
-        true => daemon::main::main().await,
-        //man, I don't remember why one is async and one isn't, but I'll figure that out another time
-        false => client::main::main(),
+    match args.command {
+        Some(Command::Diff {
+            snapshot_a,
+            snapshot_b,
+            all,
+        }) => client::diff::main(&snapshot_a, &snapshot_b, all),
+        None => match args.daemon {
+            true => daemon::main::main().await,
+            //man, I don't remember why one is async and one isn't, but I'll figure that out another time
+            false => client::main::main(),
+        },
     }
     if args.benchmark {
         println!("Execution time: {:?}", start.elapsed());
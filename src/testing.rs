@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Binary files too fiddly to build in a test, see tests/fixtures/README.md for how they were made
+pub(crate) fn checked_in(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+static FIXTURE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// A directory tree for a test to read from, like a fake /sys, removed again when dropped. Tests
+// run in parallel, so every fixture gets its own directory
+pub(crate) struct Fixture {
+    root: PathBuf,
+}
+
+impl Fixture {
+    pub(crate) fn new() -> Fixture {
+        let number: usize = FIXTURE_COUNT.fetch_add(1, Ordering::Relaxed);
+        let root: PathBuf =
+            std::env::temp_dir().join(format!("hayabusa-test-{}-{}", std::process::id(), number));
+        fs::create_dir_all(&root).expect("Failed to create fixture directory");
+        Fixture { root }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.root
+    }
+
+    // Parent directories are created as needed
+    pub(crate) fn write(&self, path: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path: PathBuf = self.root.join(path);
+        fs::create_dir_all(path.parent().expect("Fixture files have a parent"))
+            .expect("Failed to create fixture directory");
+        fs::write(&path, contents).expect("Failed to write fixture file");
+        path
+    }
+
+    pub(crate) fn create_dir(&self, path: &str) -> PathBuf {
+        let path: PathBuf = self.root.join(path);
+        fs::create_dir_all(&path).expect("Failed to create fixture directory");
+        path
+    }
+
+    // Like sysfs does it, `link` points at `target`, both within the fixture
+    #[cfg(unix)]
+    pub(crate) fn symlink(&self, target: &str, link: &str) -> PathBuf {
+        let link: PathBuf = self.root.join(link);
+        fs::create_dir_all(link.parent().expect("Fixture links have a parent"))
+            .expect("Failed to create fixture directory");
+        std::os::unix::fs::symlink(self.root.join(target), &link)
+            .expect("Failed to create fixture link");
+        link
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}